@@ -1,12 +0,0 @@
-up-to-date = Dein System ist auf dem neuesten Stand.
-updates-available = { $numberUpdates ->
-    [one] 1 { $updateSource }-Update verfügbar
-   *[other] { $numberUpdates } { $updateSource }-Updates verfügbar
-}
-news = Neuigkeiten seit dem letzten Update – Zum Löschen klicken
-no-news = Keine Neuigkeiten seit dem letzten Update.
-no-updates-available = Keine Updates verfügbar.
-loading = Wird geladen...
-last-checked = Zuletzt geprüft: { $dateTime } – Zum Aktualisieren klicken
-warning-error = Warnung: { $error }!!
-n-more = …und { $n } weitere.
@@ -0,0 +1,165 @@
+## Popup tabs
+
+controls = Steuerung
+alarms = Wecker
+settings = Einstellungen
+
+## Player
+
+no-music-playing = Keine Musik
+unknown-title = Unbekannt
+unknown-artist = Unbekannter Interpret
+no-player-selected = Kein Player ausgewählt
+select-player-hint = Wähle im Reiter Einstellungen einen Mediaplayer aus
+no-media-playing = Keine Medienwiedergabe
+start-media-hint = Starte die Wiedergabe in einem MPRIS-kompatiblen Player
+loading = Wird geladen …
+art-failed = Cover nicht verfügbar
+balance = Balance
+balance-left = L
+balance-right = R
+no-audio-stream = Für diesen Player wurde kein Audiostream gefunden
+system-volume = Systemlautstärke
+
+## Sleep timer
+
+sleep-timer = Schlummerfunktion
+pausing-in = Pause in { $time }
+pausing-at-track-end = Pause am Ende des Titels
+sleep-timer-preset = { $minutes } Min.
+end-of-track = Titelende
+minutes = Minuten
+set = Setzen
+cancel = Abbrechen
+
+## Alarms
+
+no-alarms = Keine Wecker gestellt.
+selected-player = Ausgewählter Player
+new-alarm = Neuer Wecker
+time = Uhrzeit
+one-time-alarm-hint = Für einen einmaligen Wecker alle Tage abwählen
+alarm-ramp = Einblenden über { $minutes ->
+    [one] 1 Minute
+   *[other] { $minutes } Minuten
+}
+alarm-uri = Playlist oder URI (optional)
+alarm-volume-hint = Die Lautstärke wird bis zur aktuellen Player-Lautstärke angehoben
+add-alarm = Wecker hinzufügen
+alarm-once = Einmalig
+alarm-every-day = Täglich
+weekday-mon = Mo
+weekday-tue = Di
+weekday-wed = Mi
+weekday-thu = Do
+weekday-fri = Fr
+weekday-sat = Sa
+weekday-sun = So
+
+## Settings
+
+multi-player-mode = Mehrere Player
+show-all-players = Alle Mediaplayer anzeigen
+hide-stopped-players = Gestoppte Player ausblenden
+hide-stopped-players-hint = Gestoppte Player ausblenden (laufende und pausierte bleiben sichtbar)
+volume = Lautstärke
+show-system-volume = Systemlautstärke und Ausgabe anzeigen
+max-volume-hint = Maximale Lautstärke (über 100 % wird der Audiostream verstärkt)
+restore-volume-hint = Letzte Lautstärke wiederherstellen, wenn diese Player starten:
+fading = Überblenden
+fade-on-play-pause = Beim Pausieren und Fortsetzen überblenden
+fade-duration = Überblenddauer
+fade-duration-value = { $seconds } s
+ducking = Absenken
+ducking-enabled = Musik leiser stellen, während andere Audioquellen spielen
+ducking-amount = Stärke
+ducking-on-recording = Musik auch leiser stellen, während ein Mikrofon aufnimmt
+visualizer = Visualisierung
+show-visualizer = Audiospektrum anzeigen
+visualizer-hint = Nimmt den Audiostream des Players auf, solange dieses Fenster offen ist
+refresh = Aktualisierung
+refresh-active = Aktiv: { $ms } ms
+refresh-idle = Leerlauf: { $seconds ->
+    [one] 1 Sekunde
+   *[other] { $seconds } Sekunden
+}
+refresh-hint = Wie oft Player abgefragt werden, während das Fenster offen ist oder etwas spielt, und während nichts spielt. Ohne Player wird alle 10 Sekunden gesucht.
+album-art = Albumcover
+art-cache-size = Festplatten-Cache: { $megabytes } MB
+art-cache-hint = Heruntergeladene Cover werden bis zu dieser Größe auf der Festplatte behalten, die am längsten nicht gezeigten werden zuerst entfernt
+player-discovery = Player-Suche
+auto-detect-players = Neue Player automatisch erkennen
+discover-players = Player suchen
+player-selection = Player-Auswahl
+choose-player = Wähle den zu steuernden Mediaplayer:
+discovered-players = Gefundene Player
+no-player = Keiner (alle Player deaktivieren)
+currently-playing = { $player } (♪ spielt gerade)
+no-players-discovered = Noch keine Player gefunden. Klicke auf „{ discover-players }“, um zu suchen.
+
+## Panel button
+
+panel-button = Leistensymbol
+panel-button-hint = Linksklick öffnet das Fenster, wenn kein Klick dafür eingestellt ist; Touchpad-Scrollen löst einmal pro Geste aus
+scroll-distance = Scrollweite: { $lines }
+action-none = Nichts
+action-toggle-popup = Fenster öffnen
+action-play-pause = Wiedergabe/Pause
+action-next = Nächster Titel
+action-previous = Vorheriger Titel
+action-volume-up = Lauter
+action-volume-down = Leiser
+action-seek-forward = Vorspulen
+action-seek-backward = Zurückspulen
+action-raise-player = Player in den Vordergrund
+action-next-player = Nächster Player
+action-previous-player = Vorheriger Player
+gesture-left-click = Linksklick
+gesture-middle-click = Mittelklick
+gesture-right-click = Rechtsklick
+gesture-double-click = Doppelklick
+gesture-scroll-up = Nach oben scrollen
+gesture-scroll-down = Nach unten scrollen
+gesture-scroll-left = Nach links scrollen
+gesture-scroll-right = Nach rechts scrollen
+
+## Panel text
+
+panel-text = Leistentext
+show-panel-text = Aktuellen Titel in der Leiste anzeigen
+panel-text-width = Breite: { $chars ->
+    [one] 1 Zeichen
+   *[other] { $chars } Zeichen
+}
+panel-text-marquee = Lange Titel durchlaufen lassen
+vertical-panels = Auf senkrechten Leisten
+vertical-hide = Ausblenden
+vertical-stack = Buchstaben stapeln
+
+## Player profiles
+
+player-profiles = Player-Profile
+player-profiles-hint = Wähle einen Player, um Name, Symbol, Standardlautstärke und Ausgabe festzulegen
+display-name = Anzeigename
+icon = Symbol
+icon-name-placeholder = Symbolname, z. B. spotify-client
+hide-player = Diesen Player ausblenden
+default-volume = Lautstärke beim Erscheinen setzen
+output = Ausgabe
+system-default-output = Standardausgabe des Systems
+
+## Settings file
+
+settings-file = Einstellungsdatei
+settings-file-hint = Einstellungen als TOML-Datei teilen; unbekannte oder ungültige Schlüssel werden beim Import übersprungen
+path = Pfad
+export-settings = Einstellungen exportieren
+import-settings = Einstellungen importieren
+exported-to = Exportiert nach { $path }
+export-failed = Export fehlgeschlagen: { $error }
+imported-all = Alle Einstellungen importiert
+imported-skipped = Importiert, aber { $count ->
+    [one] 1 Einstellung
+   *[other] { $count } Einstellungen
+} übersprungen: { $keys }
+import-failed = Import fehlgeschlagen: { $error }
@@ -1,12 +0,0 @@
-up-to-date = Your system is up to date.
-updates-available = { $numberUpdates ->
-    [one] 1 { $updateSource } update available
-   *[other] { $numberUpdates } { $updateSource } updates available
-}
-news = News since last update - Click to clear
-no-news = No news since last update.
-no-updates-available = No updates available.
-loading = Loading...
-last-checked = Last checked: { $dateTime } - Click to refresh
-warning-error = Warning: { $error }!!
-n-more = ...and { $n } more.
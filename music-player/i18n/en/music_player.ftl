@@ -0,0 +1,165 @@
+## Popup tabs
+
+controls = Controls
+alarms = Alarms
+settings = Settings
+
+## Player
+
+no-music-playing = No music playing
+unknown-title = Unknown
+unknown-artist = Unknown Artist
+no-player-selected = No player selected
+select-player-hint = Go to Settings tab to select a media player
+no-media-playing = No media playing
+start-media-hint = Start playing media in any MPRIS-compatible player
+loading = Loading...
+art-failed = Cover unavailable
+balance = Balance
+balance-left = L
+balance-right = R
+no-audio-stream = No audio stream found for this player
+system-volume = System volume
+
+## Sleep timer
+
+sleep-timer = Sleep timer
+pausing-in = Pausing in { $time }
+pausing-at-track-end = Pausing at the end of the track
+sleep-timer-preset = { $minutes } min
+end-of-track = End of track
+minutes = Minutes
+set = Set
+cancel = Cancel
+
+## Alarms
+
+no-alarms = No alarms set.
+selected-player = Selected player
+new-alarm = New Alarm
+time = Time
+one-time-alarm-hint = Leave all days off for a one-time alarm
+alarm-ramp = Fade in over { $minutes ->
+    [one] 1 minute
+   *[other] { $minutes } minutes
+}
+alarm-uri = Playlist or URI (optional)
+alarm-volume-hint = The volume is raised to the current player volume
+add-alarm = Add Alarm
+alarm-once = Once
+alarm-every-day = Every day
+weekday-mon = Mon
+weekday-tue = Tue
+weekday-wed = Wed
+weekday-thu = Thu
+weekday-fri = Fri
+weekday-sat = Sat
+weekday-sun = Sun
+
+## Settings
+
+multi-player-mode = Multi-Player Mode
+show-all-players = Show all media players
+hide-stopped-players = Hide stopped players
+hide-stopped-players-hint = Hide players that are stopped (keep Playing and Paused)
+volume = Volume
+show-system-volume = Show system volume and output
+max-volume-hint = Maximum volume (above 100% boosts the audio stream)
+restore-volume-hint = Restore the last volume when these players start:
+fading = Fading
+fade-on-play-pause = Fade when pausing and resuming
+fade-duration = Fade duration
+fade-duration-value = { $seconds } s
+ducking = Ducking
+ducking-enabled = Lower music while other audio plays
+ducking-amount = Amount
+ducking-on-recording = Also lower music while a microphone is recording
+visualizer = Visualizer
+show-visualizer = Show audio spectrum
+visualizer-hint = Records the player's audio stream while this popup is open
+refresh = Refresh
+refresh-active = Active: { $ms } ms
+refresh-idle = Idle: { $seconds ->
+    [one] 1 second
+   *[other] { $seconds } seconds
+}
+refresh-hint = How often players are checked while the popup is open or something plays, and while nothing plays. With no players at all they are checked every 10 seconds.
+album-art = Album Art
+art-cache-size = Disk cache: { $megabytes } MB
+art-cache-hint = Downloaded covers are kept on disk up to this size, the least recently shown are removed first
+player-discovery = Player Discovery
+auto-detect-players = Auto-detect new players
+discover-players = Discover Players
+player-selection = Player Selection
+choose-player = Choose which media player to control:
+discovered-players = Discovered Players
+no-player = None (disable all players)
+currently-playing = { $player } (♪ currently playing)
+no-players-discovered = No players discovered yet. Click “{ discover-players }” to search.
+
+## Panel button
+
+panel-button = Panel Button
+panel-button-hint = Left click opens the popup when no click is set to open it; touchpad scrolling triggers once per gesture
+scroll-distance = Scroll distance: { $lines }
+action-none = Nothing
+action-toggle-popup = Open popup
+action-play-pause = Play/pause
+action-next = Next track
+action-previous = Previous track
+action-volume-up = Volume up
+action-volume-down = Volume down
+action-seek-forward = Seek forward
+action-seek-backward = Seek backward
+action-raise-player = Raise player
+action-next-player = Next player
+action-previous-player = Previous player
+gesture-left-click = Left click
+gesture-middle-click = Middle click
+gesture-right-click = Right click
+gesture-double-click = Double click
+gesture-scroll-up = Scroll up
+gesture-scroll-down = Scroll down
+gesture-scroll-left = Scroll left
+gesture-scroll-right = Scroll right
+
+## Panel text
+
+panel-text = Panel Text
+show-panel-text = Show what is playing in the panel
+panel-text-width = Width: { $chars ->
+    [one] 1 character
+   *[other] { $chars } characters
+}
+panel-text-marquee = Scroll long titles
+vertical-panels = On vertical panels
+vertical-hide = Hide
+vertical-stack = Stack letters
+
+## Player profiles
+
+player-profiles = Player Profiles
+player-profiles-hint = Choose a player to set its name, icon, default volume and output
+display-name = Display name
+icon = Icon
+icon-name-placeholder = Icon name, e.g. spotify-client
+hide-player = Hide this player
+default-volume = Set volume when it appears
+output = Output
+system-default-output = System default output
+
+## Settings file
+
+settings-file = Settings File
+settings-file-hint = Share a setup as a TOML file; unknown or invalid keys are skipped on import
+path = Path
+export-settings = Export settings
+import-settings = Import settings
+exported-to = Exported to { $path }
+export-failed = Export failed: { $error }
+imported-all = Imported all settings
+imported-skipped = Imported, but skipped { $count ->
+    [one] 1 setting
+   *[other] { $count } settings
+}: { $keys }
+import-failed = Import failed: { $error }
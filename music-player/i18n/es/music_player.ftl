@@ -0,0 +1,165 @@
+## Popup tabs
+
+controls = Controles
+alarms = Alarmas
+settings = Ajustes
+
+## Player
+
+no-music-playing = No se reproduce música
+unknown-title = Desconocido
+unknown-artist = Artista desconocido
+no-player-selected = Ningún reproductor seleccionado
+select-player-hint = Ve a la pestaña Ajustes para elegir un reproductor
+no-media-playing = No se reproduce nada
+start-media-hint = Empieza a reproducir en cualquier reproductor compatible con MPRIS
+loading = Cargando…
+art-failed = Portada no disponible
+balance = Balance
+balance-left = I
+balance-right = D
+no-audio-stream = No se encontró ningún flujo de audio para este reproductor
+system-volume = Volumen del sistema
+
+## Sleep timer
+
+sleep-timer = Temporizador
+pausing-in = Pausa en { $time }
+pausing-at-track-end = Pausa al final de la pista
+sleep-timer-preset = { $minutes } min
+end-of-track = Fin de la pista
+minutes = Minutos
+set = Fijar
+cancel = Cancelar
+
+## Alarms
+
+no-alarms = No hay alarmas.
+selected-player = Reproductor seleccionado
+new-alarm = Nueva alarma
+time = Hora
+one-time-alarm-hint = Deja todos los días sin marcar para una alarma única
+alarm-ramp = Subir el volumen durante { $minutes ->
+    [one] 1 minuto
+   *[other] { $minutes } minutos
+}
+alarm-uri = Lista o URI (opcional)
+alarm-volume-hint = El volumen sube hasta el volumen actual del reproductor
+add-alarm = Añadir alarma
+alarm-once = Una vez
+alarm-every-day = Todos los días
+weekday-mon = lu
+weekday-tue = ma
+weekday-wed = mi
+weekday-thu = ju
+weekday-fri = vi
+weekday-sat = sá
+weekday-sun = do
+
+## Settings
+
+multi-player-mode = Varios reproductores
+show-all-players = Mostrar todos los reproductores
+hide-stopped-players = Ocultar reproductores detenidos
+hide-stopped-players-hint = Ocultar los reproductores detenidos (se mantienen los que reproducen o están en pausa)
+volume = Volumen
+show-system-volume = Mostrar el volumen y la salida del sistema
+max-volume-hint = Volumen máximo (por encima del 100 % se amplifica el flujo de audio)
+restore-volume-hint = Restaurar el último volumen cuando se inicien estos reproductores:
+fading = Fundido
+fade-on-play-pause = Fundido al pausar y reanudar
+fade-duration = Duración del fundido
+fade-duration-value = { $seconds } s
+ducking = Atenuación
+ducking-enabled = Bajar la música mientras suena otro audio
+ducking-amount = Cantidad
+ducking-on-recording = Bajar también la música mientras graba un micrófono
+visualizer = Visualizador
+show-visualizer = Mostrar el espectro de audio
+visualizer-hint = Graba el flujo de audio del reproductor mientras esta ventana está abierta
+refresh = Actualización
+refresh-active = Activo: { $ms } ms
+refresh-idle = En reposo: { $seconds ->
+    [one] 1 segundo
+   *[other] { $seconds } segundos
+}
+refresh-hint = Cada cuánto se consultan los reproductores mientras la ventana está abierta o algo suena, y mientras no suena nada. Sin reproductores se buscan cada 10 segundos.
+album-art = Portadas
+art-cache-size = Caché en disco: { $megabytes } MB
+art-cache-hint = Las portadas descargadas se guardan en disco hasta este tamaño; las que llevan más tiempo sin mostrarse se eliminan primero
+player-discovery = Búsqueda de reproductores
+auto-detect-players = Detectar nuevos reproductores automáticamente
+discover-players = Buscar reproductores
+player-selection = Selección de reproductor
+choose-player = Elige qué reproductor controlar:
+discovered-players = Reproductores encontrados
+no-player = Ninguno (desactivar todos los reproductores)
+currently-playing = { $player } (♪ reproduciendo)
+no-players-discovered = Aún no se han encontrado reproductores. Pulsa «{ discover-players }» para buscar.
+
+## Panel button
+
+panel-button = Botón del panel
+panel-button-hint = El clic izquierdo abre la ventana si ningún clic está configurado para abrirla; el desplazamiento del panel táctil actúa una vez por gesto
+scroll-distance = Distancia de desplazamiento: { $lines }
+action-none = Nada
+action-toggle-popup = Abrir ventana
+action-play-pause = Reproducir/pausar
+action-next = Pista siguiente
+action-previous = Pista anterior
+action-volume-up = Subir volumen
+action-volume-down = Bajar volumen
+action-seek-forward = Avanzar
+action-seek-backward = Retroceder
+action-raise-player = Mostrar reproductor
+action-next-player = Reproductor siguiente
+action-previous-player = Reproductor anterior
+gesture-left-click = Clic izquierdo
+gesture-middle-click = Clic central
+gesture-right-click = Clic derecho
+gesture-double-click = Doble clic
+gesture-scroll-up = Desplazar arriba
+gesture-scroll-down = Desplazar abajo
+gesture-scroll-left = Desplazar a la izquierda
+gesture-scroll-right = Desplazar a la derecha
+
+## Panel text
+
+panel-text = Texto del panel
+show-panel-text = Mostrar lo que suena en el panel
+panel-text-width = Ancho: { $chars ->
+    [one] 1 carácter
+   *[other] { $chars } caracteres
+}
+panel-text-marquee = Desplazar los títulos largos
+vertical-panels = En paneles verticales
+vertical-hide = Ocultar
+vertical-stack = Apilar letras
+
+## Player profiles
+
+player-profiles = Perfiles de reproductor
+player-profiles-hint = Elige un reproductor para fijar su nombre, icono, volumen predeterminado y salida
+display-name = Nombre visible
+icon = Icono
+icon-name-placeholder = Nombre del icono, p. ej. spotify-client
+hide-player = Ocultar este reproductor
+default-volume = Fijar el volumen cuando aparezca
+output = Salida
+system-default-output = Salida predeterminada del sistema
+
+## Settings file
+
+settings-file = Archivo de ajustes
+settings-file-hint = Comparte una configuración como archivo TOML; las claves desconocidas o no válidas se omiten al importar
+path = Ruta
+export-settings = Exportar ajustes
+import-settings = Importar ajustes
+exported-to = Exportado a { $path }
+export-failed = Error al exportar: { $error }
+imported-all = Se importaron todos los ajustes
+imported-skipped = Importado, pero se { $count ->
+    [one] omitió 1 ajuste
+   *[other] omitieron { $count } ajustes
+}: { $keys }
+import-failed = Error al importar: { $error }
@@ -1,12 +0,0 @@
-up-to-date = Ditt system är uppdaterat.
-updates-available = { $numberUpdates ->
-                     [one] 1 { $updateSource } uppdatering tillgänglig
-                     *[other] { $numberUpdates } { $updateSource } uppdateringar tillgängliga
-}
-news = Nyheter sedan senaste uppdateringen - klicka för att rensa
-no-news = Inga nyheter sedan senaste uppdateringen.
-no-updates-available = Inga uppdateringar tillgängliga.
-loading = Laddar...
-last-checked = Senast kontrollerat: { $dateTime } - klicka för att uppdatera
-warning-error = Varning: { $error }!!
-n-more = ...och { $n } mer.
@@ -1,31 +1,93 @@
-use crate::config::ConfigManager;
-use crate::music::{MusicController, PlayerInfo};
+use crate::art::{self, AlbumArt, MemoryCache};
+use crate::audio::AudioSink;
+use crate::config::{
+    Alarm, AppConfig, ConfigManager, PanelAction, PanelActions, PanelGesture, PlayerProfile,
+    VerticalPanelText,
+};
+use crate::fl;
+use crate::music::{self, MusicController, PlayerInfo};
+use crate::visualizer::{AudioLevels, Visualizer};
+use alarm::AlarmDraft;
 use cosmic::app::{Core, Task};
+use cosmic::iced::mouse::ScrollDelta;
 use cosmic::iced::platform_specific::shell::wayland::commands::popup::{destroy_popup, get_popup};
 use cosmic::iced::window::Id;
 use cosmic::iced::Limits;
 use cosmic::{Application, Element};
+use dbus_service::{ServiceState, ServiceTarget};
+use fade::{Fade, FadeDirection, FadeTarget};
+use keyboard::Shortcut;
 use mpris::PlaybackStatus;
+use panel::{Click, ClickTracker, ScrollAccumulator};
+use sleep_timer::{SleepTimer, SleepTimerLength};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+mod alarm;
+mod dbus_service;
+mod fade;
+mod keyboard;
+mod mpris_proxy;
+mod panel;
+mod sleep_timer;
 mod subscription;
 mod view;
 
+/// Decoded covers kept in memory, enough for every player card and a few recent tracks
+const ART_MEMORY_ENTRIES: usize = 32;
+
+/// Players are also rediscovered this often, for audio streams that start after their player
+const DISCOVERY_INTERVAL: Duration = Duration::from_secs(10);
+
 pub struct CosmicAppletMusic {
     core: Core,
     popup: Option<Id>,
     player_info: PlayerInfo,
     music_controller: MusicController,
     config_manager: Option<ConfigManager>,
-    album_art_handle: Option<cosmic::iced::widget::image::Handle>,
+    album_art: AlbumArt,
     current_art_url: Option<String>,
     active_tab: PopupTab,
     all_players_info: Vec<PlayerInfo>,
-    player_album_arts: std::collections::HashMap<String, cosmic::iced::widget::image::Handle>,
+    /// Art of each player in multi-player mode with the URL it was loaded from, by bus name
+    player_album_arts: std::collections::HashMap<String, (String, AlbumArt)>,
+    /// Recently loaded covers by URL, served without reading them again
+    art_cache: MemoryCache<cosmic::iced::widget::image::Handle>,
+    expanded_players: std::collections::HashSet<String>,
+    visualizer: Visualizer,
+    audio_levels: std::collections::HashMap<String, AudioLevels>,
+    output_sinks: Vec<AudioSink>,
+    output_sink_labels: Vec<String>,
+    default_sink: Option<AudioSink>,
+    fades: std::collections::HashMap<FadeTarget, Fade>,
+    next_fade_id: u64,
+    sleep_timer: Option<SleepTimer>,
+    sleep_timer_custom_minutes: String,
+    alarm_draft: AlarmDraft,
+    last_alarm_check: Option<chrono::DateTime<chrono::Local>>,
+    settings_file_path: String,
+    /// Outcome of the last settings export or import
+    settings_file_status: Option<String>,
+    /// Identity of the player whose profile is open in the Settings tab
+    editing_profile: Option<String>,
+    scroll_accumulator: ScrollAccumulator,
+    click_tracker: ClickTracker,
+    last_discovery: Option<Instant>,
+    /// Scroll position of the panel text, and the text it applies to
+    marquee_offset: usize,
+    marquee_text: String,
+    /// Bus name of the player card that keyboard shortcuts act on
+    focused_player: Option<String>,
+    /// What the D-Bus service reports
+    service_state: Arc<Mutex<ServiceState>>,
+    /// Bus name of the player the D-Bus service and MPRIS proxy last acted on
+    service_player: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PopupTab {
     Controls,
+    Alarms,
     Settings,
 }
 
@@ -37,11 +99,37 @@ impl Default for CosmicAppletMusic {
             player_info: PlayerInfo::default(),
             music_controller: MusicController::new(),
             config_manager: None,
-            album_art_handle: None,
+            album_art: AlbumArt::None,
             current_art_url: None,
             active_tab: PopupTab::Controls,
             all_players_info: Vec::new(),
             player_album_arts: std::collections::HashMap::new(),
+            art_cache: MemoryCache::new(ART_MEMORY_ENTRIES),
+            expanded_players: std::collections::HashSet::new(),
+            visualizer: Visualizer::default(),
+            audio_levels: std::collections::HashMap::new(),
+            output_sinks: Vec::new(),
+            output_sink_labels: Vec::new(),
+            default_sink: None,
+            fades: std::collections::HashMap::new(),
+            next_fade_id: 0,
+            sleep_timer: None,
+            sleep_timer_custom_minutes: String::new(),
+            alarm_draft: AlarmDraft::default(),
+            last_alarm_check: None,
+            settings_file_path: std::env::var("HOME")
+                .map(|home| format!("{}/music-player-settings.toml", home))
+                .unwrap_or_default(),
+            settings_file_status: None,
+            editing_profile: None,
+            scroll_accumulator: ScrollAccumulator::default(),
+            click_tracker: ClickTracker::default(),
+            last_discovery: None,
+            marquee_offset: 0,
+            marquee_text: String::new(),
+            focused_player: None,
+            service_state: Arc::new(Mutex::new(ServiceState::default())),
+            service_player: None,
         }
     }
 }
@@ -58,11 +146,13 @@ pub enum Message {
     FindPlayer,
     UpdateStatus(mpris::PlaybackStatus),
     VolumeChanged(f64),
-    ScrollUp,
-    ScrollDown,
-    MiddleClick,
+    VolumeReleased,
+    PanelGesture(PanelGesture),
+    /// The double-click window of a left click passed
+    PanelClickExpired(u64),
+    PanelScrolled(ScrollDelta),
     LoadAlbumArt(String),
-    AlbumArtLoaded(Option<cosmic::iced::widget::image::Handle>),
+    AlbumArtLoaded(String, AlbumArt),
     DiscoverPlayers,
     ToggleAutoDetect(bool),
     SelectPlayer(Option<String>),
@@ -71,10 +161,65 @@ pub enum Message {
     NextPlayer(String),
     PreviousPlayer(String),
     VolumeChangedPlayer(String, f64),
+    VolumeReleasedPlayer(String),
     LoadAlbumArtPlayer(String, String),
-    AlbumArtLoadedPlayer(String, Option<cosmic::iced::widget::image::Handle>),
+    AlbumArtLoadedPlayer(String, String, AlbumArt),
     ToggleShowAllPlayers(bool),
     ToggleHideInactive(bool),
+    ToggleAdvancedPlayer(String),
+    BalanceChangedPlayer(String, f64),
+    ToggleVisualizer(bool),
+    VisualizerTick,
+    SetMaxVolume(u32),
+    ToggleRememberVolume(String, bool),
+    MasterVolumeChanged(f64),
+    ToggleMasterMute,
+    SelectDefaultSink(usize),
+    ToggleShowSystemVolume(bool),
+    ToggleDucking(bool),
+    DuckingAmountChanged(u32),
+    ToggleDuckingOnRecording(bool),
+    ToggleFade(bool),
+    FadeDurationChanged(u32),
+    FadeStep(FadeTarget, u64),
+    SetSleepTimer(SleepTimerLength),
+    SleepTimerCustomChanged(String),
+    SetCustomSleepTimer,
+    CancelSleepTimer,
+    SleepTimerTick,
+    CheckAlarms,
+    AlarmTimeChanged(String),
+    ToggleAlarmWeekday(usize),
+    AlarmPlayerChanged(Option<String>),
+    AlarmUriChanged(String),
+    AlarmRampChanged(u32),
+    AddAlarm,
+    RemoveAlarm(usize),
+    ToggleAlarm(usize, bool),
+    ConfigChanged(AppConfig),
+    SettingsFilePathChanged(String),
+    ExportSettings,
+    ImportSettings,
+    EditPlayerProfile(Option<String>),
+    PlayerProfileChanged(String, PlayerProfile),
+    SetPanelAction(PanelGesture, PanelAction),
+    ScrollThresholdChanged(f32),
+    ToggleShowPanelText(bool),
+    PanelTextMaxCharsChanged(u32),
+    TogglePanelTextMarquee(bool),
+    SetVerticalPanelText(VerticalPanelText),
+    MarqueeTick,
+    RefreshIntervalChanged(u32),
+    IdleRefreshIntervalChanged(u32),
+    ArtCacheSizeChanged(u32),
+    Shortcut(Shortcut),
+    /// Calls to the D-Bus service, acting on the player it reports
+    ServicePlayPause,
+    ServiceNext,
+    ServicePrevious,
+    ServiceVolumeChanged(f64),
+    /// The applet is asked to exit
+    Shutdown,
 }
 
 impl Application for CosmicAppletMusic {
@@ -137,11 +282,12 @@ impl Application for CosmicAppletMusic {
             Message::FindPlayer => self.handle_find_player(),
             Message::UpdateStatus(status) => self.handle_update_status(status),
             Message::VolumeChanged(volume) => self.handle_volume_changed(volume),
-            Message::ScrollUp => self.handle_next(),
-            Message::ScrollDown => self.handle_previous(),
-            Message::MiddleClick => self.handle_play_pause(),
+            Message::VolumeReleased => self.handle_volume_released(),
+            Message::PanelGesture(gesture) => self.handle_panel_gesture(gesture),
+            Message::PanelClickExpired(id) => self.handle_panel_click_expired(id),
+            Message::PanelScrolled(delta) => self.handle_panel_scrolled(delta),
             Message::LoadAlbumArt(url) => self.handle_load_album_art(url),
-            Message::AlbumArtLoaded(handle) => self.handle_album_art_loaded(handle),
+            Message::AlbumArtLoaded(url, art) => self.handle_album_art_loaded(url, art),
             Message::DiscoverPlayers => self.handle_discover_players(),
             Message::ToggleAutoDetect(enabled) => self.handle_toggle_auto_detect(enabled),
             Message::SelectPlayer(player) => self.handle_select_player(player),
@@ -152,25 +298,101 @@ impl Application for CosmicAppletMusic {
             Message::VolumeChangedPlayer(bus_name, volume) => {
                 self.handle_volume_changed_player(bus_name, volume)
             }
+            Message::VolumeReleasedPlayer(bus_name) => self.handle_volume_released_player(bus_name),
             Message::LoadAlbumArtPlayer(bus_name, url) => {
                 self.handle_load_album_art_player(bus_name, url)
             }
-            Message::AlbumArtLoadedPlayer(bus_name, handle) => {
-                self.handle_album_art_loaded_player(bus_name, handle)
+            Message::AlbumArtLoadedPlayer(bus_name, url, art) => {
+                self.handle_album_art_loaded_player(bus_name, url, art)
             }
             Message::ToggleShowAllPlayers(enabled) => self.handle_toggle_show_all_players(enabled),
             Message::ToggleHideInactive(enabled) => self.handle_toggle_hide_inactive(enabled),
+            Message::ToggleAdvancedPlayer(bus_name) => self.handle_toggle_advanced_player(bus_name),
+            Message::BalanceChangedPlayer(bus_name, balance) => {
+                self.handle_balance_changed_player(bus_name, balance)
+            }
+            Message::ToggleVisualizer(enabled) => self.handle_toggle_visualizer(enabled),
+            Message::VisualizerTick => self.handle_visualizer_tick(),
+            Message::SetMaxVolume(percent) => self.handle_set_max_volume(percent),
+            Message::ToggleRememberVolume(player, enabled) => {
+                self.handle_toggle_remember_volume(player, enabled)
+            }
+            Message::MasterVolumeChanged(volume) => self.handle_master_volume_changed(volume),
+            Message::ToggleMasterMute => self.handle_toggle_master_mute(),
+            Message::SelectDefaultSink(index) => self.handle_select_default_sink(index),
+            Message::ToggleShowSystemVolume(enabled) => {
+                self.handle_toggle_show_system_volume(enabled)
+            }
+            Message::ToggleDucking(enabled) => self.handle_toggle_ducking(enabled),
+            Message::DuckingAmountChanged(percent) => self.handle_ducking_amount_changed(percent),
+            Message::ToggleDuckingOnRecording(enabled) => {
+                self.handle_toggle_ducking_on_recording(enabled)
+            }
+            Message::ToggleFade(enabled) => self.handle_toggle_fade(enabled),
+            Message::FadeDurationChanged(duration_ms) => {
+                self.handle_fade_duration_changed(duration_ms)
+            }
+            Message::FadeStep(target, id) => self.handle_fade_step(target, id),
+            Message::SetSleepTimer(length) => self.handle_set_sleep_timer(length),
+            Message::SleepTimerCustomChanged(minutes) => {
+                self.handle_sleep_timer_custom_changed(minutes)
+            }
+            Message::SetCustomSleepTimer => self.handle_set_custom_sleep_timer(),
+            Message::CancelSleepTimer => self.handle_cancel_sleep_timer(),
+            Message::SleepTimerTick => self.handle_sleep_timer_tick(),
+            Message::CheckAlarms => self.handle_check_alarms(),
+            Message::AlarmTimeChanged(time) => self.handle_alarm_time_changed(time),
+            Message::ToggleAlarmWeekday(day) => self.handle_toggle_alarm_weekday(day),
+            Message::AlarmPlayerChanged(player) => self.handle_alarm_player_changed(player),
+            Message::AlarmUriChanged(uri) => self.handle_alarm_uri_changed(uri),
+            Message::AlarmRampChanged(minutes) => self.handle_alarm_ramp_changed(minutes),
+            Message::AddAlarm => self.handle_add_alarm(),
+            Message::RemoveAlarm(index) => self.handle_remove_alarm(index),
+            Message::ToggleAlarm(index, enabled) => self.handle_toggle_alarm(index, enabled),
+            Message::ConfigChanged(config) => self.handle_config_changed(config),
+            Message::SettingsFilePathChanged(path) => self.handle_settings_file_path_changed(path),
+            Message::ExportSettings => self.handle_export_settings(),
+            Message::ImportSettings => self.handle_import_settings(),
+            Message::EditPlayerProfile(identity) => self.handle_edit_player_profile(identity),
+            Message::PlayerProfileChanged(identity, profile) => {
+                self.handle_player_profile_changed(identity, profile)
+            }
+            Message::SetPanelAction(gesture, action) => self.handle_set_panel_action(gesture, action),
+            Message::ScrollThresholdChanged(threshold) => {
+                self.handle_scroll_threshold_changed(threshold)
+            }
+            Message::ToggleShowPanelText(show) => self.handle_toggle_show_panel_text(show),
+            Message::PanelTextMaxCharsChanged(max_chars) => {
+                self.handle_panel_text_max_chars_changed(max_chars)
+            }
+            Message::TogglePanelTextMarquee(marquee) => self.handle_toggle_panel_text_marquee(marquee),
+            Message::SetVerticalPanelText(vertical) => self.handle_set_vertical_panel_text(vertical),
+            Message::MarqueeTick => self.handle_marquee_tick(),
+            Message::RefreshIntervalChanged(interval_ms) => {
+                self.handle_refresh_interval_changed(interval_ms)
+            }
+            Message::IdleRefreshIntervalChanged(interval_ms) => {
+                self.handle_idle_refresh_interval_changed(interval_ms)
+            }
+            Message::ArtCacheSizeChanged(size_mb) => self.handle_art_cache_size_changed(size_mb),
+            Message::Shortcut(shortcut) => self.handle_shortcut(shortcut),
+            Message::ServicePlayPause => self.handle_service_play_pause(),
+            Message::ServiceNext => self.handle_service_next(),
+            Message::ServicePrevious => self.handle_service_previous(),
+            Message::ServiceVolumeChanged(volume) => self.handle_service_volume_changed(volume),
+            Message::Shutdown => self.handle_shutdown(),
         }
     }
 
     fn subscription(&self) -> cosmic::iced::Subscription<Self::Message> {
-        subscription::subscription()
+        subscription::subscription(self)
     }
 }
 
 impl CosmicAppletMusic {
     fn handle_toggle_popup(&mut self) -> Task<Message> {
         if let Some(p) = self.popup.take() {
+            self.stop_visualizer();
             destroy_popup(p)
         } else {
             let new_id = Id::unique();
@@ -196,6 +418,7 @@ impl CosmicAppletMusic {
             self.popup = None;
             // Reset to controls tab when popup closes
             self.active_tab = PopupTab::Controls;
+            self.stop_visualizer();
         }
         Task::none()
     }
@@ -205,8 +428,19 @@ impl CosmicAppletMusic {
         Task::none()
     }
 
-    fn handle_play_pause(&self) -> Task<Message> {
-        let _ = self.music_controller.play_pause();
+    fn handle_play_pause(&mut self) -> Task<Message> {
+        let fade_task = match self.fade_duration() {
+            Some(duration) => self.toggle_with_fade(
+                FadeTarget::Selected,
+                self.player_info.status,
+                self.player_info.volume,
+                duration,
+            ),
+            None => {
+                let _ = self.music_controller.play_pause();
+                Task::none()
+            }
+        };
 
         // Immediately toggle the UI status for responsive feedback
         let new_status = match self.player_info.status {
@@ -215,6 +449,7 @@ impl CosmicAppletMusic {
         };
 
         Task::batch([
+            fade_task,
             Task::done(cosmic::Action::App(Message::UpdateStatus(new_status))),
             Task::done(cosmic::Action::App(Message::FindPlayer)),
         ])
@@ -236,7 +471,7 @@ impl CosmicAppletMusic {
             (None, Some(_new_url)) => true,
             (Some(old_url), Some(new_url)) => old_url != new_url,
             (Some(_), None) => {
-                self.album_art_handle = None;
+                self.album_art = AlbumArt::None;
                 self.current_art_url = None;
                 false
             }
@@ -244,6 +479,8 @@ impl CosmicAppletMusic {
         };
 
         self.player_info = info.clone();
+        self.sync_visualizer();
+        self.publish_service_state();
 
         if should_load_art {
             if let Some(url) = info.art_url {
@@ -256,6 +493,21 @@ impl CosmicAppletMusic {
     }
 
     fn handle_find_player(&mut self) -> Task<Message> {
+        // Players coming and going trigger discovery themselves, see `mpris_name_changes`
+        if self
+            .last_discovery
+            .map_or(true, |last| last.elapsed() >= DISCOVERY_INTERVAL)
+        {
+            self.discover_players();
+        }
+
+        self.update_ducking();
+
+        // Only query the outputs while the popup shows them
+        if self.popup.is_some() && self.show_system_volume() {
+            self.refresh_output_state();
+        }
+
         // Check if in multi-player mode
         let show_all_players = self
             .config_manager
@@ -265,7 +517,6 @@ impl CosmicAppletMusic {
 
         if show_all_players {
             // In multi-player mode, update all players
-            let _ = self.music_controller.discover_all_players();
             let all_players = self.music_controller.get_all_players_info();
             return Task::done(cosmic::Action::App(Message::UpdateAllPlayersInfo(
                 all_players,
@@ -273,17 +524,13 @@ impl CosmicAppletMusic {
         }
 
         // Single-player mode
-        if let Some(ref config) = self.config_manager {
-            // Use new selected player approach
-            if let Some(selected_player) = config.get_selected_player() {
-                let _ = self.music_controller.find_specific_player(&selected_player);
-            } else {
-                // No player selected - try to find any active player for backward compatibility
-                let _ = self.music_controller.find_active_player();
-            }
-        } else {
-            let _ = self.music_controller.find_active_player();
-        }
+        let selected_player = self
+            .config_manager
+            .as_ref()
+            .and_then(|config| config.get_selected_player());
+        let _ = self
+            .music_controller
+            .find_selected_player(selected_player.as_deref());
         let info = self.music_controller.get_player_info();
         Task::done(cosmic::Action::App(Message::UpdatePlayerInfo(info)))
     }
@@ -294,39 +541,60 @@ impl CosmicAppletMusic {
     }
 
     fn handle_volume_changed(&mut self, volume: f64) -> Task<Message> {
+        // The user's choice wins over a running fade
+        self.fades.remove(&FadeTarget::Selected);
         let _ = self.music_controller.set_volume(volume);
         self.player_info.volume = volume;
         Task::none()
     }
 
+    /// Remembers the volume once the slider is let go instead of on every step
+    fn handle_volume_released(&mut self) -> Task<Message> {
+        self.remember_volume();
+        Task::none()
+    }
+
+    fn remember_volume(&mut self) {
+        if let Some(ref mut config) = self.config_manager {
+            if !self.player_info.identity.is_empty() {
+                let _ = config.set_player_volume(
+                    self.player_info.identity.clone(),
+                    self.player_info.volume,
+                );
+            }
+        }
+    }
+
     fn handle_load_album_art(&mut self, url: String) -> Task<Message> {
+        if let Some(handle) = self.art_cache.get(&url) {
+            self.album_art = AlbumArt::Loaded(handle);
+            return Task::none();
+        }
+
+        self.album_art = AlbumArt::Loading;
+        let cache_limit = self.art_cache_limit_bytes();
         Task::perform(
             async move {
-                match reqwest::get(&url).await {
-                    Ok(response) => match response.bytes().await {
-                        Ok(bytes) => {
-                            let handle = cosmic::iced::widget::image::Handle::from_bytes(bytes);
-                            Some(handle)
-                        }
-                        Err(_) => None,
-                    },
-                    Err(_) => None,
-                }
+                let art = art::load(&url, cache_limit).await;
+                (url, art)
             },
-            |result| cosmic::Action::App(Message::AlbumArtLoaded(result)),
+            |(url, art)| cosmic::Action::App(Message::AlbumArtLoaded(url, art)),
         )
     }
 
-    fn handle_album_art_loaded(
-        &mut self,
-        handle: Option<cosmic::iced::widget::image::Handle>,
-    ) -> Task<Message> {
-        self.album_art_handle = handle;
+    fn handle_album_art_loaded(&mut self, url: String, art: AlbumArt) -> Task<Message> {
+        if let AlbumArt::Loaded(ref handle) = art {
+            self.art_cache.insert(url.clone(), handle.clone());
+        }
+        // Ignore art of a track that has already changed again
+        if self.current_art_url.as_ref() == Some(&url) {
+            self.album_art = art;
+        }
         Task::none()
     }
 
     fn handle_discover_players(&mut self) -> Task<Message> {
-        let _ = self.music_controller.discover_all_players();
+        self.discover_players();
 
         // Auto-add discovered players to config if auto-detect is enabled
         if let Some(ref mut config) = self.config_manager {
@@ -347,6 +615,9 @@ impl CosmicAppletMusic {
     }
 
     fn handle_select_player(&mut self, player: Option<String>) -> Task<Message> {
+        // Finish a fade on the previous player before the selection changes
+        self.complete_fade(&FadeTarget::Selected);
+
         if let Some(ref mut config) = self.config_manager {
             let _ = config.set_selected_player(player);
         }
@@ -356,18 +627,29 @@ impl CosmicAppletMusic {
     fn handle_update_all_players_info(&mut self, players_info: Vec<PlayerInfo>) -> Task<Message> {
         // Update the list of all players
         self.all_players_info = players_info.clone();
+        self.sync_visualizer();
+        self.publish_service_state();
 
-        // Load album arts for new players
+        // Forget the art of players that went away
+        self.player_album_arts.retain(|bus_name, _| {
+            players_info.iter().any(|player| &player.bus_name == bus_name)
+        });
+
+        // Load art for new players and for players whose track changed
         let mut tasks = Vec::new();
         for player in players_info {
-            if let Some(ref art_url) = player.art_url {
-                if !self.player_album_arts.contains_key(&player.bus_name) {
-                    let bus_name = player.bus_name.clone();
-                    let url = art_url.clone();
-                    tasks.push(Task::done(cosmic::Action::App(
-                        Message::LoadAlbumArtPlayer(bus_name, url),
-                    )));
-                }
+            let Some(url) = player.art_url else {
+                self.player_album_arts.remove(&player.bus_name);
+                continue;
+            };
+            let loaded_url = self
+                .player_album_arts
+                .get(&player.bus_name)
+                .map(|(loaded_url, _)| loaded_url);
+            if loaded_url != Some(&url) {
+                tasks.push(Task::done(cosmic::Action::App(
+                    Message::LoadAlbumArtPlayer(player.bus_name, url),
+                )));
             }
         }
 
@@ -375,10 +657,22 @@ impl CosmicAppletMusic {
     }
 
     fn handle_play_pause_player(&mut self, bus_name: String) -> Task<Message> {
-        let _ = self.music_controller.play_pause_player(&bus_name);
+        let fade_task = match (self.fade_duration(), self.find_player_info(&bus_name)) {
+            (Some(duration), Some((status, volume))) => self.toggle_with_fade(
+                FadeTarget::Player(bus_name.clone()),
+                status,
+                volume,
+                duration,
+            ),
+            _ => {
+                let _ = self.music_controller.play_pause_player(&bus_name);
+                Task::none()
+            }
+        };
 
         // Update the player info
         Task::batch([
+            fade_task,
             Task::done(cosmic::Action::App(Message::DiscoverPlayers)),
             Task::done(cosmic::Action::App(Message::UpdateAllPlayersInfo(
                 self.music_controller.get_all_players_info(),
@@ -407,6 +701,8 @@ impl CosmicAppletMusic {
     }
 
     fn handle_volume_changed_player(&mut self, bus_name: String, volume: f64) -> Task<Message> {
+        // The user's choice wins over a running fade
+        self.fades.remove(&FadeTarget::Player(bus_name.clone()));
         let _ = self.music_controller.set_volume_player(&bus_name, volume);
 
         // Update the player info in the list
@@ -421,22 +717,36 @@ impl CosmicAppletMusic {
         Task::none()
     }
 
+    fn handle_volume_released_player(&mut self, bus_name: String) -> Task<Message> {
+        self.remember_volume_player(&bus_name);
+        Task::none()
+    }
+
+    fn remember_volume_player(&mut self, bus_name: &str) {
+        let Some(player) = self.all_players_info.iter().find(|p| p.bus_name == bus_name) else {
+            return;
+        };
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_player_volume(player.identity.clone(), player.volume);
+        }
+    }
+
     fn handle_load_album_art_player(&mut self, bus_name: String, url: String) -> Task<Message> {
+        if let Some(handle) = self.art_cache.get(&url) {
+            self.player_album_arts.insert(bus_name, (url, AlbumArt::Loaded(handle)));
+            return Task::none();
+        }
+
+        // Marked as loading so the next refresh does not request it again
+        self.player_album_arts.insert(bus_name.clone(), (url.clone(), AlbumArt::Loading));
+        let cache_limit = self.art_cache_limit_bytes();
         Task::perform(
             async move {
-                match reqwest::get(&url).await {
-                    Ok(response) => match response.bytes().await {
-                        Ok(bytes) => {
-                            let handle = cosmic::iced::widget::image::Handle::from_bytes(bytes);
-                            (bus_name, Some(handle))
-                        }
-                        Err(_) => (bus_name, None),
-                    },
-                    Err(_) => (bus_name, None),
-                }
+                let art = art::load(&url, cache_limit).await;
+                (bus_name, url, art)
             },
-            |(bus_name, handle)| {
-                cosmic::Action::App(Message::AlbumArtLoadedPlayer(bus_name, handle))
+            |(bus_name, url, art)| {
+                cosmic::Action::App(Message::AlbumArtLoadedPlayer(bus_name, url, art))
             },
         )
     }
@@ -444,10 +754,17 @@ impl CosmicAppletMusic {
     fn handle_album_art_loaded_player(
         &mut self,
         bus_name: String,
-        handle: Option<cosmic::iced::widget::image::Handle>,
+        url: String,
+        art: AlbumArt,
     ) -> Task<Message> {
-        if let Some(handle) = handle {
-            self.player_album_arts.insert(bus_name, handle);
+        if let AlbumArt::Loaded(ref handle) = art {
+            self.art_cache.insert(url.clone(), handle.clone());
+        }
+        // Ignore art of a track the player has already moved on from, or of a player that left
+        if let Some((loaded_url, player_art)) = self.player_album_arts.get_mut(&bus_name) {
+            if *loaded_url == url {
+                *player_art = art;
+            }
         }
         Task::none()
     }
@@ -476,4 +793,1172 @@ impl CosmicAppletMusic {
         }
         Task::none()
     }
+
+    fn handle_toggle_advanced_player(&mut self, bus_name: String) -> Task<Message> {
+        if !self.expanded_players.remove(&bus_name) {
+            self.expanded_players.insert(bus_name);
+        }
+        Task::none()
+    }
+
+    fn handle_balance_changed_player(&mut self, bus_name: String, balance: f64) -> Task<Message> {
+        let _ = self.music_controller.set_balance_player(&bus_name, balance);
+
+        // Update the player info in the list
+        if let Some(player) = self
+            .all_players_info
+            .iter_mut()
+            .find(|p| p.bus_name == bus_name)
+        {
+            player.balance = Some(balance);
+        }
+
+        Task::none()
+    }
+
+    fn handle_toggle_visualizer(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_show_visualizer(enabled);
+        }
+        self.sync_visualizer();
+        Task::none()
+    }
+
+    fn handle_visualizer_tick(&mut self) -> Task<Message> {
+        self.audio_levels = self.visualizer.levels();
+        Task::none()
+    }
+
+    /// Captures the streams of playing players, but only while the popup is open
+    fn sync_visualizer(&mut self) {
+        let enabled = self
+            .config_manager
+            .as_ref()
+            .map(|config| config.get_show_visualizer())
+            .unwrap_or(false);
+
+        if !enabled || self.popup.is_none() {
+            self.stop_visualizer();
+            return;
+        }
+
+        let show_all_players = self
+            .config_manager
+            .as_ref()
+            .map(|config| config.get_show_all_players())
+            .unwrap_or(false);
+
+        let players: Vec<&PlayerInfo> = if show_all_players {
+            self.all_players_info.iter().collect()
+        } else {
+            vec![&self.player_info]
+        };
+
+        let streams: std::collections::HashMap<String, u32> = players
+            .into_iter()
+            .filter(|player| player.status == PlaybackStatus::Playing)
+            .filter_map(|player| Some((player.bus_name.clone(), player.stream_index?)))
+            .collect();
+
+        self.visualizer.sync_streams(&streams);
+        self.audio_levels.retain(|bus_name, _| streams.contains_key(bus_name));
+    }
+
+    fn stop_visualizer(&mut self) {
+        self.visualizer.stop();
+        self.audio_levels.clear();
+    }
+
+    fn handle_set_max_volume(&mut self, percent: u32) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_max_volume_percent(percent);
+        }
+        Task::none()
+    }
+
+    fn max_volume(&self) -> f64 {
+        self.config_manager
+            .as_ref()
+            .map(|config| config.get_max_volume())
+            .unwrap_or(1.0)
+    }
+
+    fn handle_toggle_remember_volume(&mut self, player: String, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_remember_volume(player, enabled);
+        }
+        Task::none()
+    }
+
+    /// Rediscovers all players and applies the defaults of those that appeared
+    fn discover_players(&mut self) {
+        self.last_discovery = Some(Instant::now());
+        if let Ok(appeared) = self.music_controller.discover_all_players() {
+            self.apply_player_defaults(&appeared);
+        }
+    }
+
+    /// Restores the remembered (or profile default) volume and output of newly appeared players
+    fn apply_player_defaults(&mut self, bus_names: &[String]) {
+        let Some(ref config) = self.config_manager else {
+            return;
+        };
+
+        for bus_name in bus_names {
+            let Some(identity) = self.music_controller.get_player_identity(bus_name) else {
+                continue;
+            };
+            let profile = config.get_player_profile(&identity);
+
+            let volume = config
+                .get_restorable_volume(&identity)
+                .or_else(|| profile.and_then(|profile| profile.default_volume));
+            if let Some(volume) = volume {
+                let _ = self.music_controller.set_volume_player(bus_name, volume);
+            }
+
+            if let Some(sink_name) = profile.and_then(|profile| profile.preferred_output.as_ref()) {
+                let _ = self.music_controller.move_player_to_output(bus_name, sink_name);
+            }
+        }
+    }
+
+    fn handle_master_volume_changed(&mut self, volume: f64) -> Task<Message> {
+        if let Some(audio_ctrl) = self.music_controller.audio_controller() {
+            let _ = audio_ctrl.set_default_sink_volume(volume);
+        }
+        if let Some(ref mut sink) = self.default_sink {
+            sink.volume = volume;
+        }
+        Task::none()
+    }
+
+    fn handle_toggle_master_mute(&mut self) -> Task<Message> {
+        if let Some(ref mut sink) = self.default_sink {
+            sink.muted = !sink.muted;
+            if let Some(audio_ctrl) = self.music_controller.audio_controller() {
+                let _ = audio_ctrl.set_default_sink_mute(sink.muted);
+            }
+        }
+        Task::none()
+    }
+
+    fn handle_select_default_sink(&mut self, index: usize) -> Task<Message> {
+        if let Some(sink) = self.output_sinks.get(index) {
+            if let Some(audio_ctrl) = self.music_controller.audio_controller() {
+                let _ = audio_ctrl.set_default_sink(&sink.name);
+            }
+        }
+        self.refresh_output_state();
+        Task::none()
+    }
+
+    fn handle_toggle_show_system_volume(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_show_system_volume(enabled);
+        }
+        if enabled {
+            self.refresh_output_state();
+        }
+        Task::none()
+    }
+
+    fn show_system_volume(&self) -> bool {
+        self.config_manager
+            .as_ref()
+            .map(|config| config.get_show_system_volume())
+            .unwrap_or(true)
+    }
+
+    fn refresh_output_state(&mut self) {
+        let Some(audio_ctrl) = self.music_controller.audio_controller() else {
+            return;
+        };
+        if audio_ctrl.refresh_sinks().is_err() {
+            return;
+        }
+
+        self.output_sinks = audio_ctrl.get_sinks();
+        self.output_sink_labels = self
+            .output_sinks
+            .iter()
+            .map(|sink| {
+                if sink.description.is_empty() {
+                    sink.name.clone()
+                } else {
+                    sink.description.clone()
+                }
+            })
+            .collect();
+        self.default_sink = audio_ctrl.get_default_sink();
+    }
+
+    fn handle_toggle_ducking(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_ducking_enabled(enabled);
+        }
+        Task::none()
+    }
+
+    fn handle_ducking_amount_changed(&mut self, percent: u32) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_ducking_amount_percent(percent);
+        }
+        Task::none()
+    }
+
+    fn handle_toggle_ducking_on_recording(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_ducking_on_recording(enabled);
+        }
+        Task::none()
+    }
+
+    fn handle_shutdown(&mut self) -> Task<Message> {
+        // Faded or ducked music would otherwise stay lowered after the applet is gone
+        let targets: Vec<FadeTarget> = self.fades.keys().cloned().collect();
+        for target in targets {
+            self.complete_fade(&target);
+        }
+        self.cancel_sleep_timer();
+        self.music_controller.restore_ducking();
+        cosmic::iced::exit()
+    }
+
+    /// Ducks music while other audio plays; when disabled, still restores streams that are ducked
+    fn update_ducking(&self) {
+        let (amount, duck_on_recording) = match self.config_manager {
+            Some(ref config) if config.get_ducking_enabled() => (
+                f64::from(config.get_ducking_amount_percent()) / 100.0,
+                config.get_ducking_on_recording(),
+            ),
+            _ => (0.0, false),
+        };
+
+        let _ = self.music_controller.update_ducking(amount, duck_on_recording);
+    }
+
+    fn handle_toggle_fade(&mut self, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_fade_on_play_pause(enabled);
+        }
+        Task::none()
+    }
+
+    fn handle_fade_duration_changed(&mut self, duration_ms: u32) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_fade_duration_ms(duration_ms);
+        }
+        Task::none()
+    }
+
+    fn handle_fade_step(&mut self, target: FadeTarget, id: u64) -> Task<Message> {
+        // Ignore steps of fades that were cancelled or replaced
+        let Some(fade) = self.fades.get_mut(&target).filter(|fade| fade.id == id) else {
+            return Task::none();
+        };
+
+        if !fade.advance() {
+            let volume = fade.volume();
+            self.set_target_volume(&target, volume);
+            return Self::schedule_fade_step(target, id);
+        }
+
+        self.complete_fade(&target);
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    /// Fade duration when fading on play/pause is enabled
+    fn fade_duration(&self) -> Option<Duration> {
+        self.config_manager
+            .as_ref()
+            .filter(|config| config.get_fade_on_play_pause())
+            .map(|config| Duration::from_millis(u64::from(config.get_fade_duration_ms())))
+    }
+
+    fn find_player_info(&self, bus_name: &str) -> Option<(PlaybackStatus, f64)> {
+        self.all_players_info
+            .iter()
+            .find(|p| p.bus_name == bus_name)
+            .map(|p| (p.status, p.volume))
+    }
+
+    /// Starts fading out (when playing) or in (when paused); pressing again while a fade
+    /// runs reverses it, keeping the volume from before the first press
+    fn toggle_with_fade(
+        &mut self,
+        target: FadeTarget,
+        status: PlaybackStatus,
+        volume: f64,
+        duration: Duration,
+    ) -> Task<Message> {
+        if let Some(fade) = self.fades.get_mut(&target) {
+            fade.reverse();
+            return Task::none();
+        }
+
+        let direction = if status == PlaybackStatus::Playing {
+            FadeDirection::Out
+        } else {
+            FadeDirection::In
+        };
+
+        if direction == FadeDirection::In {
+            return self.start_fade_in(target, volume, duration);
+        }
+
+        let id = self.next_fade_id();
+        self.fades.insert(target.clone(), Fade::new(id, direction, volume, duration));
+        Self::schedule_fade_step(target, id)
+    }
+
+    /// Resumes playback from silence and raises the volume to `volume`
+    fn start_fade_in(&mut self, target: FadeTarget, volume: f64, duration: Duration) -> Task<Message> {
+        self.set_target_volume(&target, 0.0);
+        match target {
+            FadeTarget::Selected => {
+                let _ = self.music_controller.play();
+            }
+            FadeTarget::Player(ref bus_name) => {
+                let _ = self.music_controller.play_player(bus_name);
+            }
+        }
+
+        let id = self.next_fade_id();
+        self.fades.insert(target.clone(), Fade::new(id, FadeDirection::In, volume, duration));
+        Self::schedule_fade_step(target, id)
+    }
+
+    fn next_fade_id(&mut self) -> u64 {
+        let id = self.next_fade_id;
+        self.next_fade_id += 1;
+        id
+    }
+
+    /// Ends a fade right away: pauses after a fade-out and restores the original volume
+    fn complete_fade(&mut self, target: &FadeTarget) {
+        let Some(fade) = self.fades.remove(target) else {
+            return;
+        };
+
+        if fade.direction == FadeDirection::Out {
+            match target {
+                FadeTarget::Selected => {
+                    let _ = self.music_controller.pause();
+                }
+                FadeTarget::Player(bus_name) => {
+                    let _ = self.music_controller.pause_player(bus_name);
+                }
+            }
+        }
+
+        self.set_target_volume(target, fade.original_volume);
+    }
+
+    fn set_target_volume(&self, target: &FadeTarget, volume: f64) {
+        match target {
+            FadeTarget::Selected => {
+                let _ = self.music_controller.set_volume(volume);
+            }
+            FadeTarget::Player(bus_name) => {
+                let _ = self.music_controller.set_volume_player(bus_name, volume);
+            }
+        }
+    }
+
+    fn schedule_fade_step(target: FadeTarget, id: u64) -> Task<Message> {
+        Task::perform(tokio::time::sleep(fade::FADE_STEP), move |_| {
+            cosmic::Action::App(Message::FadeStep(target, id))
+        })
+    }
+
+    fn handle_set_sleep_timer(&mut self, length: SleepTimerLength) -> Task<Message> {
+        // Restore volumes a previous timer may already have lowered
+        self.cancel_sleep_timer();
+
+        let show_all_players = self
+            .config_manager
+            .as_ref()
+            .map(|config| config.get_show_all_players())
+            .unwrap_or(false);
+
+        let targets = if show_all_players {
+            // Pause whatever is playing, or every player if nothing is
+            let playing: Vec<FadeTarget> = self
+                .all_players_info
+                .iter()
+                .filter(|p| p.status == PlaybackStatus::Playing)
+                .map(|p| FadeTarget::Player(p.bus_name.clone()))
+                .collect();
+            if playing.is_empty() {
+                self.all_players_info
+                    .iter()
+                    .map(|p| FadeTarget::Player(p.bus_name.clone()))
+                    .collect()
+            } else {
+                playing
+            }
+        } else {
+            vec![FadeTarget::Selected]
+        };
+
+        self.sleep_timer = Some(SleepTimer::new(length, targets));
+        self.handle_sleep_timer_tick()
+    }
+
+    fn handle_sleep_timer_custom_changed(&mut self, minutes: String) -> Task<Message> {
+        self.sleep_timer_custom_minutes = minutes;
+        Task::none()
+    }
+
+    fn handle_set_custom_sleep_timer(&mut self) -> Task<Message> {
+        match self.sleep_timer_custom_minutes.trim().parse::<u32>() {
+            Ok(minutes) if minutes > 0 => {
+                self.handle_set_sleep_timer(SleepTimerLength::Minutes(minutes))
+            }
+            _ => Task::none(),
+        }
+    }
+
+    fn handle_cancel_sleep_timer(&mut self) -> Task<Message> {
+        self.cancel_sleep_timer();
+        Task::none()
+    }
+
+    fn cancel_sleep_timer(&mut self) {
+        if let Some(timer) = self.sleep_timer.take() {
+            for (target, volume) in timer.original_volumes {
+                self.set_target_volume(&target, volume);
+            }
+        }
+    }
+
+    fn handle_sleep_timer_tick(&mut self) -> Task<Message> {
+        let Some(mut timer) = self.sleep_timer.take() else {
+            return Task::none();
+        };
+
+        let progress = match timer.targets.first() {
+            Some(FadeTarget::Selected) => self.music_controller.get_track_progress(),
+            Some(FadeTarget::Player(bus_name)) => {
+                self.music_controller.get_track_progress_player(bus_name)
+            }
+            None => None,
+        };
+        timer.update(progress);
+
+        // Lower the volume gradually during the last minute
+        let factor = timer.volume_factor();
+        if factor < 1.0 {
+            for target in &timer.targets {
+                let original_volume = match timer.original_volumes.get(target) {
+                    Some(volume) => *volume,
+                    None => {
+                        let volume = self.target_volume(target);
+                        timer.original_volumes.insert(target.clone(), volume);
+                        volume
+                    }
+                };
+                self.set_target_volume(target, original_volume * factor);
+            }
+        }
+
+        if !timer.is_finished() {
+            self.sleep_timer = Some(timer);
+            return Task::none();
+        }
+
+        // Pause, then restore the volume for next time
+        for target in &timer.targets {
+            match target {
+                FadeTarget::Selected => {
+                    let _ = self.music_controller.pause();
+                }
+                FadeTarget::Player(bus_name) => {
+                    let _ = self.music_controller.pause_player(bus_name);
+                }
+            }
+        }
+        for (target, volume) in &timer.original_volumes {
+            self.set_target_volume(target, *volume);
+        }
+
+        Task::done(cosmic::Action::App(Message::FindPlayer))
+    }
+
+    fn target_volume(&self, target: &FadeTarget) -> f64 {
+        match target {
+            FadeTarget::Selected => self.player_info.volume,
+            FadeTarget::Player(bus_name) => self
+                .find_player_info(bus_name)
+                .map(|(_, volume)| volume)
+                .unwrap_or(1.0),
+        }
+    }
+
+    fn handle_check_alarms(&mut self) -> Task<Message> {
+        let now = chrono::Local::now();
+        let Some(since) = self.last_alarm_check.replace(now) else {
+            return Task::none();
+        };
+        let Some(ref config) = self.config_manager else {
+            return Task::none();
+        };
+
+        let due: Vec<(usize, Alarm)> = config
+            .get_alarms()
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| alarm::is_due(a, since, now))
+            .map(|(index, a)| (index, a.clone()))
+            .collect();
+
+        let mut tasks = Vec::new();
+        for (index, alarm) in due {
+            // One-time alarms switch themselves off
+            if alarm.weekdays.is_empty() {
+                if let Some(ref mut config) = self.config_manager {
+                    let _ = config.set_alarm_enabled(index, false);
+                }
+            }
+            tasks.push(self.fire_alarm(&alarm));
+        }
+
+        Task::batch(tasks)
+    }
+
+    /// Starts the alarm's player (optionally on its URI) and ramps the volume up
+    fn fire_alarm(&mut self, alarm: &Alarm) -> Task<Message> {
+        let _ = self.music_controller.discover_all_players();
+
+        let identity = alarm.player.clone().or_else(|| {
+            self.config_manager
+                .as_ref()
+                .and_then(|config| config.get_selected_player())
+        });
+        let bus_name = identity.and_then(|identity| self.music_controller.find_bus_name(&identity));
+
+        let Some(bus_name) = bus_name else {
+            // The player is not running, let the desktop open the URI instead
+            if let Some(ref uri) = alarm.uri {
+                let _ = std::process::Command::new("xdg-open").arg(uri).spawn();
+            }
+            return Task::none();
+        };
+
+        let open_uri = match alarm.uri.clone() {
+            Some(uri) => Task::perform(music::open_uri(bus_name.clone(), uri), |_| {
+                cosmic::Action::None
+            }),
+            None => Task::none(),
+        };
+
+        let ramp = Duration::from_secs(u64::from(alarm.ramp_minutes) * 60);
+        Task::batch([
+            open_uri,
+            self.start_fade_in(FadeTarget::Player(bus_name), alarm.volume, ramp),
+            Task::done(cosmic::Action::App(Message::FindPlayer)),
+        ])
+    }
+
+    fn handle_alarm_time_changed(&mut self, time: String) -> Task<Message> {
+        self.alarm_draft.time = time;
+        Task::none()
+    }
+
+    fn handle_toggle_alarm_weekday(&mut self, day: usize) -> Task<Message> {
+        if let Some(enabled) = self.alarm_draft.weekdays.get_mut(day) {
+            *enabled = !*enabled;
+        }
+        Task::none()
+    }
+
+    fn handle_alarm_player_changed(&mut self, player: Option<String>) -> Task<Message> {
+        self.alarm_draft.player = player;
+        Task::none()
+    }
+
+    fn handle_alarm_uri_changed(&mut self, uri: String) -> Task<Message> {
+        self.alarm_draft.uri = uri;
+        Task::none()
+    }
+
+    fn handle_alarm_ramp_changed(&mut self, minutes: u32) -> Task<Message> {
+        self.alarm_draft.ramp_minutes = minutes;
+        Task::none()
+    }
+
+    fn handle_add_alarm(&mut self) -> Task<Message> {
+        let volume = self.player_info.volume.clamp(0.1, 1.0);
+        if let Some(alarm) = self.alarm_draft.to_alarm(volume) {
+            if let Some(ref mut config) = self.config_manager {
+                let _ = config.add_alarm(alarm);
+            }
+            self.alarm_draft = AlarmDraft::default();
+        }
+        Task::none()
+    }
+
+    fn handle_remove_alarm(&mut self, index: usize) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.remove_alarm(index);
+        }
+        Task::none()
+    }
+
+    fn handle_toggle_alarm(&mut self, index: usize, enabled: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_alarm_enabled(index, enabled);
+        }
+        Task::none()
+    }
+
+    fn handle_config_changed(&mut self, app_config: AppConfig) -> Task<Message> {
+        let changed = self
+            .config_manager
+            .as_mut()
+            .map(|config| config.reload(app_config))
+            .unwrap_or(false);
+
+        if changed {
+            self.sync_visualizer();
+            self.update_ducking();
+        }
+        Task::none()
+    }
+
+    fn handle_settings_file_path_changed(&mut self, path: String) -> Task<Message> {
+        self.settings_file_path = path;
+        self.settings_file_status = None;
+        Task::none()
+    }
+
+    fn handle_export_settings(&mut self) -> Task<Message> {
+        let Some(ref config) = self.config_manager else {
+            return Task::none();
+        };
+
+        let path = std::path::Path::new(self.settings_file_path.trim());
+        self.settings_file_status = Some(match config.export_settings(path) {
+            Ok(()) => fl!("exported-to", path = path.display().to_string()),
+            Err(e) => fl!("export-failed", error = e.to_string()),
+        });
+        Task::none()
+    }
+
+    fn handle_import_settings(&mut self) -> Task<Message> {
+        let Some(ref mut config) = self.config_manager else {
+            return Task::none();
+        };
+
+        let path = std::path::Path::new(self.settings_file_path.trim());
+        self.settings_file_status = Some(match config.import_settings(path) {
+            Ok(rejected) if rejected.is_empty() => fl!("imported-all"),
+            Ok(rejected) => {
+                let keys: Vec<String> = rejected
+                    .iter()
+                    .map(|rejected| format!("{} ({})", rejected.key, rejected.reason))
+                    .collect();
+                fl!(
+                    "imported-skipped",
+                    count = keys.len(),
+                    keys = keys.join(", ")
+                )
+            }
+            Err(e) => fl!("import-failed", error = e.to_string()),
+        });
+
+        self.sync_visualizer();
+        self.update_ducking();
+        Task::none()
+    }
+
+    fn handle_edit_player_profile(&mut self, identity: Option<String>) -> Task<Message> {
+        // The output list is only kept fresh while the system volume is shown
+        if identity.is_some() {
+            self.refresh_output_state();
+        }
+        self.editing_profile = identity;
+        Task::none()
+    }
+
+    fn handle_player_profile_changed(&mut self, identity: String, profile: PlayerProfile) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_player_profile(identity, profile);
+        }
+        Task::none()
+    }
+
+    fn panel_actions(&self) -> PanelActions {
+        self.config_manager
+            .as_ref()
+            .map(|config| config.get_panel_actions().clone())
+            .unwrap_or_default()
+    }
+
+    fn handle_panel_gesture(&mut self, gesture: PanelGesture) -> Task<Message> {
+        let actions = self.panel_actions();
+        let action = actions.get(gesture);
+
+        // A left click only acts once it is clear no double-click follows
+        if gesture == PanelGesture::LeftClick
+            && actions.get(PanelGesture::DoubleClick) != PanelAction::None
+        {
+            return match self.click_tracker.press() {
+                Click::Pending(id) => Task::perform(
+                    tokio::time::sleep(panel::DOUBLE_CLICK_WINDOW),
+                    move |_| cosmic::Action::App(Message::PanelClickExpired(id)),
+                ),
+                Click::Double => self.run_panel_action(actions.get(PanelGesture::DoubleClick)),
+            };
+        }
+
+        // An unmapped right click still cancels a running sleep timer
+        if gesture == PanelGesture::RightClick
+            && action == PanelAction::None
+            && self.sleep_timer.is_some()
+        {
+            return self.handle_cancel_sleep_timer();
+        }
+
+        self.run_panel_action(action)
+    }
+
+    fn handle_panel_click_expired(&mut self, id: u64) -> Task<Message> {
+        if !self.click_tracker.expire(id) {
+            return Task::none();
+        }
+        let action = self.panel_actions().get(PanelGesture::LeftClick);
+        self.run_panel_action(action)
+    }
+
+    fn handle_panel_scrolled(&mut self, delta: ScrollDelta) -> Task<Message> {
+        let threshold = self.panel_actions().scroll_threshold;
+        match self.scroll_accumulator.push(delta, threshold, Instant::now()) {
+            Some(gesture) => self.handle_panel_gesture(gesture),
+            None => Task::none(),
+        }
+    }
+
+    fn run_panel_action(&mut self, action: PanelAction) -> Task<Message> {
+        match action {
+            PanelAction::None => Task::none(),
+            PanelAction::TogglePopup => self.handle_toggle_popup(),
+            PanelAction::PlayPause => self.handle_play_pause(),
+            PanelAction::Next => self.handle_next(),
+            PanelAction::Previous => self.handle_previous(),
+            PanelAction::VolumeUp => {
+                let volume = (self.player_info.volume + panel::VOLUME_STEP).min(self.max_volume());
+                let task = self.handle_volume_changed(volume);
+                self.remember_volume();
+                task
+            }
+            PanelAction::VolumeDown => {
+                let volume = (self.player_info.volume - panel::VOLUME_STEP).max(0.0);
+                let task = self.handle_volume_changed(volume);
+                self.remember_volume();
+                task
+            }
+            PanelAction::SeekForward => {
+                let _ = self.music_controller.seek(panel::SEEK_STEP);
+                Task::none()
+            }
+            PanelAction::SeekBackward => {
+                let _ = self.music_controller.seek(-panel::SEEK_STEP);
+                Task::none()
+            }
+            PanelAction::RaisePlayer => {
+                let _ = self.music_controller.raise();
+                Task::none()
+            }
+            PanelAction::NextPlayer => self.cycle_player(1),
+            PanelAction::PreviousPlayer => self.cycle_player(-1),
+        }
+    }
+
+    /// Selects the next (or previous) visible discovered player
+    fn cycle_player(&mut self, step: isize) -> Task<Message> {
+        let Some(ref config) = self.config_manager else {
+            return Task::none();
+        };
+
+        let mut identities: Vec<String> = self
+            .music_controller
+            .get_discovered_players()
+            .into_iter()
+            .map(|player| player.identity)
+            .filter(|identity| !config.is_player_hidden(identity))
+            .collect();
+        if identities.is_empty() {
+            return Task::none();
+        }
+        identities.sort();
+
+        let selected = config.get_selected_player();
+        let next = match identities.iter().position(|identity| Some(identity) == selected.as_ref()) {
+            Some(index) => (index as isize + step).rem_euclid(identities.len() as isize) as usize,
+            None => 0,
+        };
+
+        self.handle_select_player(Some(identities.swap_remove(next)))
+    }
+
+    fn handle_set_panel_action(&mut self, gesture: PanelGesture, action: PanelAction) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_panel_action(gesture, action);
+        }
+        Task::none()
+    }
+
+    fn handle_scroll_threshold_changed(&mut self, threshold: f32) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_scroll_threshold(threshold);
+        }
+        Task::none()
+    }
+
+    fn is_player_visible(&self, identity: &str) -> bool {
+        self.config_manager
+            .as_ref()
+            .map(|config| !config.is_player_hidden(identity))
+            .unwrap_or(true)
+    }
+
+    /// The player the panel button stands for: the selected one, or in multi-player
+    /// mode the first visible one that is playing
+    fn panel_player(&self) -> Option<&PlayerInfo> {
+        let show_all_players = self
+            .config_manager
+            .as_ref()
+            .map(|config| config.get_show_all_players())
+            .unwrap_or(false);
+
+        if show_all_players {
+            self.all_players_info
+                .iter()
+                .find(|p| p.status == PlaybackStatus::Playing && self.is_player_visible(&p.identity))
+        } else if self.player_info.identity.is_empty() {
+            None
+        } else {
+            Some(&self.player_info)
+        }
+    }
+
+    /// Untruncated "artist – title" for the panel, if panel text is enabled
+    fn panel_text(&self) -> Option<String> {
+        let config = self.config_manager.as_ref()?;
+        if !config.get_show_panel_text() {
+            return None;
+        }
+        if !self.core.applet.is_horizontal()
+            && config.get_vertical_panel_text() == VerticalPanelText::Hide
+        {
+            return None;
+        }
+
+        let player = self.panel_player()?;
+        if player.status == PlaybackStatus::Stopped || player.title.is_empty() {
+            return None;
+        }
+        Some(panel::format_now_playing(&player.artist, &player.title))
+    }
+
+    fn panel_text_scrolls(&self) -> bool {
+        let Some(ref config) = self.config_manager else {
+            return false;
+        };
+        config.get_panel_text_marquee()
+            && self.panel_text().is_some_and(|text| {
+                text.chars().count() > config.get_panel_text_max_chars() as usize
+            })
+    }
+
+    fn handle_toggle_show_panel_text(&mut self, show: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_show_panel_text(show);
+        }
+        Task::none()
+    }
+
+    fn handle_panel_text_max_chars_changed(&mut self, max_chars: u32) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_panel_text_max_chars(max_chars);
+        }
+        Task::none()
+    }
+
+    fn handle_toggle_panel_text_marquee(&mut self, marquee: bool) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_panel_text_marquee(marquee);
+        }
+        Task::none()
+    }
+
+    fn handle_set_vertical_panel_text(&mut self, vertical: VerticalPanelText) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_vertical_panel_text(vertical);
+        }
+        Task::none()
+    }
+
+    fn handle_marquee_tick(&mut self) -> Task<Message> {
+        let text = self.panel_text().unwrap_or_default();
+
+        // A new track starts scrolling from its beginning
+        if text != self.marquee_text {
+            self.marquee_text = text;
+            self.marquee_offset = 0;
+        } else {
+            self.marquee_offset = self.marquee_offset.wrapping_add(1);
+        }
+        Task::none()
+    }
+
+    fn handle_refresh_interval_changed(&mut self, interval_ms: u32) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_refresh_interval_ms(interval_ms);
+        }
+        Task::none()
+    }
+
+    fn handle_idle_refresh_interval_changed(&mut self, interval_ms: u32) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_idle_refresh_interval_ms(interval_ms);
+        }
+        Task::none()
+    }
+
+    fn handle_art_cache_size_changed(&mut self, size_mb: u32) -> Task<Message> {
+        if let Some(ref mut config) = self.config_manager {
+            let _ = config.set_art_cache_size_mb(size_mb);
+        }
+        // A lowered limit applies right away instead of on the next download
+        Task::perform(art::cache::trim(self.art_cache_limit_bytes()), |_| {
+            cosmic::Action::None
+        })
+    }
+
+    /// Disk space the album art cache may use
+    fn art_cache_limit_bytes(&self) -> u64 {
+        let size_mb = self
+            .config_manager
+            .as_ref()
+            .map(|config| config.get_art_cache_size_mb())
+            .unwrap_or(AppConfig::default().art_cache_size_mb);
+        u64::from(size_mb) * 1024 * 1024
+    }
+
+    /// Player cards shown in multi-player mode, in display order
+    fn visible_player_cards(&self) -> Vec<&PlayerInfo> {
+        let hide_inactive = self
+            .config_manager
+            .as_ref()
+            .map(|config| config.get_hide_inactive_players())
+            .unwrap_or(false);
+
+        self.all_players_info
+            .iter()
+            .filter(|player| !hide_inactive || player.status != PlaybackStatus::Stopped)
+            .filter(|player| self.is_player_visible(&player.identity))
+            .collect()
+    }
+
+    fn handle_shortcut(&mut self, shortcut: Shortcut) -> Task<Message> {
+        match shortcut {
+            Shortcut::Close => return self.handle_toggle_popup(),
+            Shortcut::NextTab | Shortcut::PreviousTab => {
+                let tabs = [PopupTab::Controls, PopupTab::Alarms, PopupTab::Settings];
+                let current = tabs.iter().position(|tab| *tab == self.active_tab).unwrap_or(0);
+                let next = if shortcut == Shortcut::NextTab {
+                    (current + 1) % tabs.len()
+                } else {
+                    (current + tabs.len() - 1) % tabs.len()
+                };
+                return self.handle_switch_tab(tabs[next]);
+            }
+            Shortcut::FocusNextCard | Shortcut::FocusPreviousCard => {
+                let cards: Vec<String> = self
+                    .visible_player_cards()
+                    .iter()
+                    .map(|player| player.bus_name.clone())
+                    .collect();
+                if cards.is_empty() {
+                    self.focused_player = None;
+                    return Task::none();
+                }
+
+                let current = self
+                    .focused_player
+                    .as_ref()
+                    .and_then(|focused| cards.iter().position(|bus_name| bus_name == focused));
+                let next = match (current, shortcut) {
+                    (None, _) => 0,
+                    (Some(index), Shortcut::FocusNextCard) => (index + 1) % cards.len(),
+                    (Some(index), _) => (index + cards.len() - 1) % cards.len(),
+                };
+                self.focused_player = cards.into_iter().nth(next);
+                return Task::none();
+            }
+            _ => {}
+        }
+
+        // Playback shortcuts only make sense on the Controls tab
+        if self.active_tab != PopupTab::Controls {
+            return Task::none();
+        }
+
+        let show_all_players = self
+            .config_manager
+            .as_ref()
+            .map(|config| config.get_show_all_players())
+            .unwrap_or(false);
+
+        if !show_all_players {
+            return match shortcut {
+                Shortcut::PlayPause => self.handle_play_pause(),
+                Shortcut::Next => self.handle_next(),
+                Shortcut::Previous => self.handle_previous(),
+                Shortcut::VolumeUp => self.run_panel_action(PanelAction::VolumeUp),
+                Shortcut::VolumeDown => self.run_panel_action(PanelAction::VolumeDown),
+                _ => Task::none(),
+            };
+        }
+
+        // In multi-player mode shortcuts act on the focused card, or the first one
+        let cards = self.visible_player_cards();
+        let target = cards
+            .iter()
+            .find(|player| Some(&player.bus_name) == self.focused_player.as_ref())
+            .or(cards.first())
+            .map(|player| (player.bus_name.clone(), player.volume));
+        let Some((bus_name, volume)) = target else {
+            return Task::none();
+        };
+
+        match shortcut {
+            Shortcut::PlayPause => self.handle_play_pause_player(bus_name),
+            Shortcut::Next => self.handle_next_player(bus_name),
+            Shortcut::Previous => self.handle_previous_player(bus_name),
+            Shortcut::VolumeUp => {
+                let volume = (volume + panel::VOLUME_STEP).min(self.max_volume());
+                let task = self.handle_volume_changed_player(bus_name.clone(), volume);
+                self.remember_volume_player(&bus_name);
+                task
+            }
+            Shortcut::VolumeDown => {
+                let volume = (volume - panel::VOLUME_STEP).max(0.0);
+                let task = self.handle_volume_changed_player(bus_name.clone(), volume);
+                self.remember_volume_player(&bus_name);
+                task
+            }
+            _ => Task::none(),
+        }
+    }
+
+    /// Player the D-Bus service and MPRIS proxy act on
+    ///
+    /// In multi-player mode the panel only follows a playing player, so the last one is kept
+    /// while it is around, otherwise a player paused through them could not be resumed.
+    fn service_player(&self) -> Option<&PlayerInfo> {
+        self.panel_player().or_else(|| {
+            let show_all_players = self
+                .config_manager
+                .as_ref()
+                .is_some_and(|config| config.get_show_all_players());
+            if !show_all_players {
+                return None;
+            }
+            let bus_name = self.service_player.as_ref()?;
+            self.all_players_info
+                .iter()
+                .find(|player| {
+                    player.bus_name == *bus_name && self.is_player_visible(&player.identity)
+                })
+        })
+    }
+
+    /// Player the service reports, `None` when there is none in multi-player mode
+    fn service_target(&self) -> Option<ServiceTarget> {
+        let show_all_players = self
+            .config_manager
+            .as_ref()
+            .is_some_and(|config| config.get_show_all_players());
+        if !show_all_players {
+            return Some(ServiceTarget::Selected);
+        }
+        self.service_player()
+            .map(|player| ServiceTarget::Player(player.bus_name.clone()))
+    }
+
+    fn handle_service_play_pause(&mut self) -> Task<Message> {
+        match self.service_target() {
+            Some(ServiceTarget::Selected) => self.handle_play_pause(),
+            Some(ServiceTarget::Player(bus_name)) => self.handle_play_pause_player(bus_name),
+            None => Task::none(),
+        }
+    }
+
+    fn handle_service_next(&mut self) -> Task<Message> {
+        match self.service_target() {
+            Some(ServiceTarget::Selected) => self.handle_next(),
+            Some(ServiceTarget::Player(bus_name)) => self.handle_next_player(bus_name),
+            None => Task::none(),
+        }
+    }
+
+    fn handle_service_previous(&mut self) -> Task<Message> {
+        match self.service_target() {
+            Some(ServiceTarget::Selected) => self.handle_previous(),
+            Some(ServiceTarget::Player(bus_name)) => self.handle_previous_player(bus_name),
+            None => Task::none(),
+        }
+    }
+
+    /// Sets and remembers the volume, like the slider does once let go
+    fn handle_service_volume_changed(&mut self, volume: f64) -> Task<Message> {
+        let volume = volume.clamp(0.0, self.max_volume());
+        match self.service_target() {
+            Some(ServiceTarget::Selected) => {
+                let task = self.handle_volume_changed(volume);
+                self.remember_volume();
+                task
+            }
+            Some(ServiceTarget::Player(bus_name)) => {
+                let task = self.handle_volume_changed_player(bus_name.clone(), volume);
+                self.remember_volume_player(&bus_name);
+                task
+            }
+            None => Task::none(),
+        }
+    }
+
+    /// Shares the controlled player and selection with the D-Bus service
+    fn publish_service_state(&mut self) {
+        let player = self.service_player().cloned().unwrap_or_default();
+        self.service_player = Some(player.bus_name.clone()).filter(|bus_name| !bus_name.is_empty());
+        let (selected_player, show_all_players) = self
+            .config_manager
+            .as_ref()
+            .map(|config| {
+                (
+                    config.get_selected_player().unwrap_or_default(),
+                    config.get_show_all_players(),
+                )
+            })
+            .unwrap_or_default();
+
+        if let Ok(mut state) = self.service_state.lock() {
+            *state = ServiceState {
+                title: player.title,
+                artist: player.artist,
+                status: format!("{:?}", player.status),
+                volume: player.volume,
+                identity: player.identity,
+                bus_name: player.bus_name,
+                selected_player,
+                show_all_players,
+                max_volume: self.max_volume(),
+            };
+        }
+    }
 }
@@ -0,0 +1,244 @@
+use crate::config::Alarm;
+use crate::fl;
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
+
+/// Alarms missed by more than this (e.g. while suspended) are skipped
+const MAX_ALARM_DELAY_MINUTES: i64 = 5;
+
+/// Short name of a weekday counted from 0 (Monday)
+pub fn weekday_label(day: u32) -> String {
+    match day {
+        0 => fl!("weekday-mon"),
+        1 => fl!("weekday-tue"),
+        2 => fl!("weekday-wed"),
+        3 => fl!("weekday-thu"),
+        4 => fl!("weekday-fri"),
+        5 => fl!("weekday-sat"),
+        _ => fl!("weekday-sun"),
+    }
+}
+
+/// An alarm being composed in the popup before it is saved
+#[derive(Debug, Clone)]
+pub struct AlarmDraft {
+    /// "HH:MM"
+    pub time: String,
+    /// Repeat days, Monday first
+    pub weekdays: [bool; 7],
+    pub player: Option<String>,
+    pub uri: String,
+    pub ramp_minutes: u32,
+}
+
+impl Default for AlarmDraft {
+    fn default() -> Self {
+        Self {
+            time: "07:00".to_string(),
+            weekdays: [true, true, true, true, true, false, false],
+            player: None,
+            uri: String::new(),
+            ramp_minutes: 3,
+        }
+    }
+}
+
+impl AlarmDraft {
+    /// Builds the alarm, or `None` if the time is not a valid "HH:MM"
+    pub fn to_alarm(&self, volume: f64) -> Option<Alarm> {
+        let (hour, minute) = self.time.trim().split_once(':')?;
+        let hour: u32 = hour.trim().parse().ok().filter(|h| *h < 24)?;
+        let minute: u32 = minute.trim().parse().ok().filter(|m| *m < 60)?;
+
+        Some(Alarm {
+            enabled: true,
+            hour,
+            minute,
+            weekdays: (0..7).filter(|day| self.weekdays[*day as usize]).collect(),
+            player: self.player.clone(),
+            uri: Some(self.uri.trim().to_string()).filter(|uri| !uri.is_empty()),
+            ramp_minutes: self.ramp_minutes,
+            volume,
+        })
+    }
+}
+
+/// Whether the alarm went off after `since` and up to `now`
+pub fn is_due(alarm: &Alarm, since: DateTime<Local>, now: DateTime<Local>) -> bool {
+    if !alarm.enabled {
+        return false;
+    }
+
+    // Also look at yesterday so alarms right before midnight are not missed
+    let today = now.date_naive();
+    [today.pred_opt(), Some(today)]
+        .into_iter()
+        .flatten()
+        .filter_map(|date| alarm_time_on(alarm, date))
+        .any(|time| {
+            time > since
+                && time <= now
+                && (now - time).num_minutes() < MAX_ALARM_DELAY_MINUTES
+        })
+}
+
+fn alarm_time_on(alarm: &Alarm, date: NaiveDate) -> Option<DateTime<Local>> {
+    let weekday = date.weekday().num_days_from_monday();
+    if !alarm.weekdays.is_empty() && !alarm.weekdays.contains(&weekday) {
+        return None;
+    }
+
+    let naive = date.and_hms_opt(alarm.hour, alarm.minute, 0)?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// "07:30 · Mon Tue Wed" style summary of an alarm
+pub fn describe(alarm: &Alarm) -> String {
+    let days = if alarm.weekdays.is_empty() {
+        fl!("alarm-once")
+    } else if alarm.weekdays.len() == 7 {
+        fl!("alarm-every-day")
+    } else {
+        alarm
+            .weekdays
+            .iter()
+            .map(|day| weekday_label(*day))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    format!("{:02}:{:02} · {}", alarm.hour, alarm.minute, days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A time on June `day`, 2025; June 2 is a Monday
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 6, day, hour, minute, 0)
+            .single()
+            .unwrap()
+    }
+
+    fn alarm(hour: u32, minute: u32, weekdays: Vec<u32>) -> Alarm {
+        Alarm {
+            enabled: true,
+            hour,
+            minute,
+            weekdays,
+            player: None,
+            uri: None,
+            ramp_minutes: 3,
+            volume: 0.5,
+        }
+    }
+
+    #[test]
+    fn due_once_its_time_passed() {
+        let alarm = alarm(7, 30, vec![]);
+        assert!(is_due(&alarm, at(2, 7, 29), at(2, 7, 30)));
+        assert!(is_due(&alarm, at(2, 7, 29), at(2, 7, 31)));
+        // Not before its time, and not again on the next check
+        assert!(!is_due(&alarm, at(2, 7, 28), at(2, 7, 29)));
+        assert!(!is_due(&alarm, at(2, 7, 30), at(2, 7, 31)));
+    }
+
+    #[test]
+    fn alarm_before_midnight_is_due_after_midnight() {
+        let alarm = alarm(23, 59, vec![]);
+        assert!(is_due(&alarm, at(2, 23, 58), at(3, 0, 1)));
+    }
+
+    #[test]
+    fn weekday_alarm_is_only_due_on_its_days() {
+        // Monday and Wednesday
+        let alarm = alarm(7, 0, vec![0, 2]);
+        assert!(is_due(&alarm, at(2, 6, 59), at(2, 7, 0)));
+        assert!(!is_due(&alarm, at(3, 6, 59), at(3, 7, 0)));
+        assert!(is_due(&alarm, at(4, 6, 59), at(4, 7, 0)));
+        assert!(!is_due(&alarm, at(7, 6, 59), at(7, 7, 0)));
+    }
+
+    #[test]
+    fn weekday_of_an_alarm_before_midnight_is_the_day_it_was_set_for() {
+        // Sunday night only, checked on Monday morning
+        let alarm = alarm(23, 59, vec![6]);
+        assert!(is_due(&alarm, at(1, 23, 58), at(2, 0, 1)));
+        assert!(!is_due(&alarm, at(2, 23, 58), at(3, 0, 1)));
+    }
+
+    #[test]
+    fn alarm_missed_by_too_much_is_skipped() {
+        let alarm = alarm(7, 0, vec![]);
+        // Suspended from 6:00 to 7:20
+        assert!(!is_due(&alarm, at(2, 6, 0), at(2, 7, 20)));
+        // Still rung when the check comes a little late
+        assert!(is_due(&alarm, at(2, 6, 0), at(2, 7, 4)));
+        assert!(!is_due(
+            &alarm,
+            at(2, 6, 0),
+            at(2, 7, MAX_ALARM_DELAY_MINUTES as u32)
+        ));
+    }
+
+    #[test]
+    fn disabled_alarm_is_never_due() {
+        let alarm = Alarm {
+            enabled: false,
+            ..alarm(7, 0, vec![])
+        };
+        assert!(!is_due(&alarm, at(2, 6, 59), at(2, 7, 0)));
+    }
+
+    #[test]
+    fn one_time_alarm_is_due_on_any_day() {
+        let alarm = alarm(7, 0, vec![]);
+        for day in 2..=8 {
+            assert!(is_due(&alarm, at(day, 6, 59), at(day, 7, 0)));
+        }
+    }
+
+    fn draft(time: &str) -> AlarmDraft {
+        AlarmDraft {
+            time: time.to_string(),
+            ..AlarmDraft::default()
+        }
+    }
+
+    #[test]
+    fn draft_time_is_parsed() {
+        let alarm = draft("07:30").to_alarm(0.6).unwrap();
+        assert_eq!((alarm.hour, alarm.minute), (7, 30));
+        assert_eq!(alarm.weekdays, [0, 1, 2, 3, 4]);
+        assert_eq!(alarm.volume, 0.6);
+        assert!(alarm.enabled);
+
+        let alarm = draft(" 7:5 ").to_alarm(0.6).unwrap();
+        assert_eq!((alarm.hour, alarm.minute), (7, 5));
+
+        let alarm = draft("23:59").to_alarm(0.6).unwrap();
+        assert_eq!((alarm.hour, alarm.minute), (23, 59));
+    }
+
+    #[test]
+    fn invalid_draft_times_are_rejected() {
+        for time in [
+            "24:00", "12:60", "abc", "", "7", "7:", ":30", "-1:00", "7:30:00",
+        ] {
+            assert!(draft(time).to_alarm(0.6).is_none(), "{} was accepted", time);
+        }
+    }
+
+    #[test]
+    fn draft_uri_is_trimmed_and_optional() {
+        let mut alarm_draft = draft("07:00");
+        assert_eq!(alarm_draft.to_alarm(0.5).unwrap().uri, None);
+
+        alarm_draft.uri = "  spotify:playlist:morning ".to_string();
+        assert_eq!(
+            alarm_draft.to_alarm(0.5).unwrap().uri.as_deref(),
+            Some("spotify:playlist:morning")
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! The applet's own `com.cosmic.MusicPlayer` D-Bus service, so scripts can see which
+//! player the applet controls and drive it through the same messages as the popup.
+
+use super::Message;
+use futures::channel::mpsc;
+use std::any::TypeId;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zbus::{connection, fdo, interface, object_server::SignalEmitter};
+
+pub const SERVICE_NAME: &str = "com.cosmic.MusicPlayer";
+pub const OBJECT_PATH: &str = "/com/cosmic/MusicPlayer";
+
+/// How often the published state is compared for change signals
+const STATE_POLL: Duration = Duration::from_millis(500);
+
+/// What the service reports, written by the applet after each player update
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServiceState {
+    pub title: String,
+    pub artist: String,
+    /// "Playing", "Paused" or "Stopped"
+    pub status: String,
+    pub volume: f64,
+    pub identity: String,
+    pub bus_name: String,
+    pub selected_player: String,
+    pub show_all_players: bool,
+    /// Highest volume `SetVolume` accepts, the configured maximum
+    pub max_volume: f64,
+}
+
+/// Player a call to the service acts on
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceTarget {
+    /// The selected player, in single-player mode
+    Selected,
+    /// A player of the multi-player view, by bus name
+    Player(String),
+}
+
+struct MusicPlayerService {
+    state: Arc<Mutex<ServiceState>>,
+    output: mpsc::Sender<Message>,
+}
+
+impl MusicPlayerService {
+    fn state(&self) -> ServiceState {
+        self.state.lock().map(|state| state.clone()).unwrap_or_default()
+    }
+
+    fn send(&mut self, message: Message) -> fdo::Result<()> {
+        self.output
+            .try_send(message)
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+}
+
+#[interface(name = "com.cosmic.MusicPlayer")]
+impl MusicPlayerService {
+    fn play_pause(&mut self) -> fdo::Result<()> {
+        self.send(Message::ServicePlayPause)
+    }
+
+    fn next(&mut self) -> fdo::Result<()> {
+        self.send(Message::ServiceNext)
+    }
+
+    fn previous(&mut self) -> fdo::Result<()> {
+        self.send(Message::ServicePrevious)
+    }
+
+    fn set_volume(&mut self, volume: f64) -> fdo::Result<()> {
+        let max_volume = self.state().max_volume;
+        if !(0.0..=max_volume).contains(&volume) {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "volume must be between 0.0 and {}",
+                max_volume
+            )));
+        }
+        self.send(Message::ServiceVolumeChanged(volume))
+    }
+
+    /// Selects a player by identity, an empty string clears the selection
+    fn select_player(&mut self, identity: String) -> fdo::Result<()> {
+        let player = Some(identity).filter(|identity| !identity.is_empty());
+        self.send(Message::SelectPlayer(player))
+    }
+
+    fn toggle_show_all(&mut self) -> fdo::Result<()> {
+        let show_all_players = !self.state().show_all_players;
+        self.send(Message::ToggleShowAllPlayers(show_all_players))
+    }
+
+    #[zbus(property)]
+    fn title(&self) -> String {
+        self.state().title
+    }
+
+    #[zbus(property)]
+    fn artist(&self) -> String {
+        self.state().artist
+    }
+
+    #[zbus(property)]
+    fn status(&self) -> String {
+        self.state().status
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        self.state().volume
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> String {
+        self.state().identity
+    }
+
+    #[zbus(property)]
+    fn bus_name(&self) -> String {
+        self.state().bus_name
+    }
+
+    #[zbus(property)]
+    fn selected_player(&self) -> String {
+        self.state().selected_player
+    }
+
+    #[zbus(property)]
+    fn show_all_players(&self) -> bool {
+        self.state().show_all_players
+    }
+
+    /// Emitted when the player the applet controls changes
+    #[zbus(signal)]
+    async fn player_changed(emitter: &SignalEmitter<'_>, identity: &str) -> zbus::Result<()>;
+}
+
+pub fn subscription(state: Arc<Mutex<ServiceState>>) -> cosmic::iced::Subscription<Message> {
+    cosmic::iced::Subscription::run_with_id(
+        TypeId::of::<MusicPlayerService>(),
+        cosmic::iced::stream::channel(16, move |output| async move {
+            if let Err(e) = serve(state, output).await {
+                eprintln!("Failed to publish the D-Bus service: {}", e);
+            }
+            futures::future::pending::<()>().await;
+        }),
+    )
+}
+
+async fn serve(state: Arc<Mutex<ServiceState>>, output: mpsc::Sender<Message>) -> zbus::Result<()> {
+    let service = MusicPlayerService {
+        state: state.clone(),
+        output,
+    };
+    let connection = connection::Builder::session()?
+        .name(SERVICE_NAME)?
+        .serve_at(OBJECT_PATH, service)?
+        .build()
+        .await?;
+    let iface = connection
+        .object_server()
+        .interface::<_, MusicPlayerService>(OBJECT_PATH)
+        .await?;
+
+    // Turn state changes into PropertiesChanged signals
+    let mut published = ServiceState::default();
+    loop {
+        tokio::time::sleep(STATE_POLL).await;
+
+        let current = state.lock().map(|state| state.clone()).unwrap_or_default();
+        if current == published {
+            continue;
+        }
+
+        let emitter = iface.signal_emitter();
+        let service = iface.get().await;
+        if current.title != published.title {
+            service.title_changed(emitter).await?;
+        }
+        if current.artist != published.artist {
+            service.artist_changed(emitter).await?;
+        }
+        if current.status != published.status {
+            service.status_changed(emitter).await?;
+        }
+        if current.volume != published.volume {
+            service.volume_changed(emitter).await?;
+        }
+        if current.bus_name != published.bus_name {
+            service.bus_name_changed(emitter).await?;
+        }
+        if current.selected_player != published.selected_player {
+            service.selected_player_changed(emitter).await?;
+        }
+        if current.show_all_players != published.show_all_players {
+            service.show_all_players_changed(emitter).await?;
+        }
+        if current.identity != published.identity {
+            service.identity_changed(emitter).await?;
+            MusicPlayerService::player_changed(emitter, &current.identity).await?;
+        }
+
+        published = current;
+    }
+}
@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+/// Interval between two volume changes of a fade
+pub const FADE_STEP: Duration = Duration::from_millis(50);
+
+/// Which player a fade applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FadeTarget {
+    /// The player chosen in single-player mode
+    Selected,
+    /// A player card in multi-player mode, by bus name
+    Player(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FadeDirection {
+    /// Lower the volume, then pause
+    Out,
+    /// Resume, then raise the volume
+    In,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fade {
+    /// Identifies the chain of scheduled steps driving this fade
+    pub id: u64,
+    pub direction: FadeDirection,
+    /// Volume before the fade started, restored once it ends
+    pub original_volume: f64,
+    /// Current fraction of `original_volume`, from 0.0 to 1.0
+    pub level: f64,
+    pub duration: Duration,
+}
+
+impl Fade {
+    pub fn new(id: u64, direction: FadeDirection, original_volume: f64, duration: Duration) -> Self {
+        Self {
+            id,
+            direction,
+            original_volume,
+            level: match direction {
+                FadeDirection::Out => 1.0,
+                FadeDirection::In => 0.0,
+            },
+            duration,
+        }
+    }
+
+    /// Turns a fade-out into a fade-in (or back) from its current level
+    pub fn reverse(&mut self) {
+        self.direction = match self.direction {
+            FadeDirection::Out => FadeDirection::In,
+            FadeDirection::In => FadeDirection::Out,
+        };
+    }
+
+    /// Moves one step further, returns true once the fade is complete
+    pub fn advance(&mut self) -> bool {
+        let step = FADE_STEP.as_secs_f64() / self.duration.max(FADE_STEP).as_secs_f64();
+
+        match self.direction {
+            FadeDirection::Out => {
+                self.level = (self.level - step).max(0.0);
+                self.level <= 0.0
+            }
+            FadeDirection::In => {
+                self.level = (self.level + step).min(1.0);
+                self.level >= 1.0
+            }
+        }
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.original_volume * self.level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Four steps long
+    const DURATION: Duration = Duration::from_millis(200);
+
+    fn assert_volume(fade: &Fade, expected: f64) {
+        assert!(
+            (fade.volume() - expected).abs() < 1e-9,
+            "volume {} instead of {}",
+            fade.volume(),
+            expected
+        );
+    }
+
+    #[test]
+    fn fade_out_lowers_the_volume_step_by_step() {
+        let mut fade = Fade::new(1, FadeDirection::Out, 0.8, DURATION);
+        assert_volume(&fade, 0.8);
+
+        assert!(!fade.advance());
+        assert_volume(&fade, 0.6);
+        assert!(!fade.advance());
+        assert_volume(&fade, 0.4);
+        assert!(!fade.advance());
+        assert!(fade.advance());
+        assert_volume(&fade, 0.0);
+    }
+
+    #[test]
+    fn fade_in_raises_the_volume_to_the_original() {
+        let mut fade = Fade::new(1, FadeDirection::In, 0.8, DURATION);
+        assert_volume(&fade, 0.0);
+
+        assert!(!fade.advance());
+        assert_volume(&fade, 0.2);
+        assert!(!fade.advance());
+        assert!(!fade.advance());
+        assert!(fade.advance());
+        assert_volume(&fade, 0.8);
+    }
+
+    #[test]
+    fn fade_shorter_than_a_step_completes_at_once() {
+        let mut fade = Fade::new(1, FadeDirection::Out, 0.5, Duration::ZERO);
+        assert!(fade.advance());
+        assert_volume(&fade, 0.0);
+    }
+
+    #[test]
+    fn reversed_fade_continues_from_its_level() {
+        let mut fade = Fade::new(1, FadeDirection::Out, 0.8, DURATION);
+        fade.advance();
+        fade.advance();
+        fade.reverse();
+        assert_eq!(fade.direction, FadeDirection::In);
+        assert_volume(&fade, 0.4);
+
+        assert!(!fade.advance());
+        assert!(fade.advance());
+        assert_volume(&fade, 0.8);
+    }
+
+    #[test]
+    fn interrupted_fade_keeps_the_volume_to_restore() {
+        let mut fade = Fade::new(1, FadeDirection::Out, 0.73, DURATION);
+        fade.advance();
+        fade.reverse();
+        fade.advance();
+        fade.reverse();
+        fade.advance();
+        assert!(fade.volume() < 0.73);
+        assert_eq!(fade.original_volume, 0.73);
+    }
+}
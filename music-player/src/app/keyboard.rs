@@ -0,0 +1,40 @@
+use super::Message;
+use cosmic::iced::keyboard::{key::Named, Key, Modifiers};
+
+/// Popup keyboard shortcuts; keys typed into a text input never get here
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shortcut {
+    PlayPause,
+    Next,
+    Previous,
+    VolumeUp,
+    VolumeDown,
+    /// Moves the focus ring to the next (or previous) player card
+    FocusNextCard,
+    FocusPreviousCard,
+    NextTab,
+    PreviousTab,
+    Close,
+}
+
+pub fn shortcut(key: Key, modifiers: Modifiers) -> Option<Message> {
+    let Key::Named(named) = key else {
+        return None;
+    };
+
+    let shortcut = match named {
+        Named::Space => Shortcut::PlayPause,
+        Named::ArrowRight => Shortcut::Next,
+        Named::ArrowLeft => Shortcut::Previous,
+        Named::ArrowUp => Shortcut::VolumeUp,
+        Named::ArrowDown => Shortcut::VolumeDown,
+        Named::Tab if modifiers.control() && modifiers.shift() => Shortcut::PreviousTab,
+        Named::Tab if modifiers.control() => Shortcut::NextTab,
+        Named::Tab if modifiers.shift() => Shortcut::FocusPreviousCard,
+        Named::Tab => Shortcut::FocusNextCard,
+        Named::Escape => Shortcut::Close,
+        _ => return None,
+    };
+
+    Some(Message::Shortcut(shortcut))
+}
@@ -0,0 +1,363 @@
+//! An MPRIS player of the applet's own that forwards every call to the player the applet
+//! targets, so media keys and other MPRIS clients follow the applet's selection.
+
+use super::dbus_service::ServiceState;
+use super::Message;
+use crate::music::PROXY_PLAYER_NAME;
+use futures::StreamExt;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zbus::object_server::SignalEmitter;
+use zbus::proxy::SignalStream;
+use zbus::zvariant::{DynamicType, ObjectPath, OwnedValue, Value};
+use zbus::{connection, fdo, interface, Connection, Proxy};
+
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// How often the target is checked for changes to mirror
+const STATE_POLL: Duration = Duration::from_millis(500);
+
+/// The applet's current target, looked up again on every call since the selection can change
+#[derive(Clone)]
+struct Target {
+    connection: Connection,
+    state: Arc<Mutex<ServiceState>>,
+}
+
+impl Target {
+    fn bus_name(&self) -> Option<String> {
+        let state = self.state.lock().ok()?;
+        if state.bus_name.is_empty() || state.bus_name == PROXY_PLAYER_NAME {
+            return None;
+        }
+        Some(format!("org.mpris.MediaPlayer2.{}", state.bus_name))
+    }
+
+    async fn proxy(&self, interface: &'static str) -> Option<Proxy<'static>> {
+        let bus_name = self.bus_name()?;
+        Proxy::new(&self.connection, bus_name, MPRIS_PATH, interface)
+            .await
+            .ok()
+    }
+
+    async fn call<B>(&self, interface: &'static str, method: &'static str, body: &B) -> fdo::Result<()>
+    where
+        B: serde::Serialize + DynamicType,
+    {
+        let Some(proxy) = self.proxy(interface).await else {
+            return Ok(());
+        };
+        proxy.call_method(method, body).await?;
+        Ok(())
+    }
+
+    async fn get<T>(&self, interface: &'static str, property: &'static str) -> Option<T>
+    where
+        T: TryFrom<OwnedValue>,
+        T::Error: Into<zbus::Error>,
+    {
+        self.proxy(interface).await?.get_property(property).await.ok()
+    }
+
+    async fn set(&self, property: &'static str, value: Value<'static>) -> zbus::Result<()> {
+        match self.proxy(PLAYER_INTERFACE).await {
+            Some(proxy) => proxy.set_property(property, value).await.map_err(Into::into),
+            None => Ok(()),
+        }
+    }
+}
+
+struct RootProxy {
+    target: Target,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl RootProxy {
+    async fn raise(&self) -> fdo::Result<()> {
+        self.target.call(ROOT_INTERFACE, "Raise", &()).await
+    }
+
+    async fn quit(&self) -> fdo::Result<()> {
+        self.target.call(ROOT_INTERFACE, "Quit", &()).await
+    }
+
+    #[zbus(property)]
+    async fn can_quit(&self) -> bool {
+        self.target.get(ROOT_INTERFACE, "CanQuit").await.unwrap_or(false)
+    }
+
+    #[zbus(property)]
+    async fn can_raise(&self) -> bool {
+        self.target.get(ROOT_INTERFACE, "CanRaise").await.unwrap_or(false)
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> String {
+        "COSMIC Music Player".to_string()
+    }
+
+    #[zbus(property)]
+    async fn supported_uri_schemes(&self) -> Vec<String> {
+        self.target
+            .get(ROOT_INTERFACE, "SupportedUriSchemes")
+            .await
+            .unwrap_or_default()
+    }
+
+    #[zbus(property)]
+    async fn supported_mime_types(&self) -> Vec<String> {
+        self.target
+            .get(ROOT_INTERFACE, "SupportedMimeTypes")
+            .await
+            .unwrap_or_default()
+    }
+}
+
+struct PlayerProxy {
+    target: Target,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl PlayerProxy {
+    async fn next(&self) -> fdo::Result<()> {
+        self.target.call(PLAYER_INTERFACE, "Next", &()).await
+    }
+
+    async fn previous(&self) -> fdo::Result<()> {
+        self.target.call(PLAYER_INTERFACE, "Previous", &()).await
+    }
+
+    async fn pause(&self) -> fdo::Result<()> {
+        self.target.call(PLAYER_INTERFACE, "Pause", &()).await
+    }
+
+    async fn play_pause(&self) -> fdo::Result<()> {
+        self.target.call(PLAYER_INTERFACE, "PlayPause", &()).await
+    }
+
+    async fn stop(&self) -> fdo::Result<()> {
+        self.target.call(PLAYER_INTERFACE, "Stop", &()).await
+    }
+
+    async fn play(&self) -> fdo::Result<()> {
+        self.target.call(PLAYER_INTERFACE, "Play", &()).await
+    }
+
+    async fn seek(&self, offset: i64) -> fdo::Result<()> {
+        self.target.call(PLAYER_INTERFACE, "Seek", &(offset,)).await
+    }
+
+    async fn set_position(&self, track_id: ObjectPath<'_>, position: i64) -> fdo::Result<()> {
+        self.target
+            .call(PLAYER_INTERFACE, "SetPosition", &(track_id, position))
+            .await
+    }
+
+    async fn open_uri(&self, uri: String) -> fdo::Result<()> {
+        self.target.call(PLAYER_INTERFACE, "OpenUri", &(uri,)).await
+    }
+
+    #[zbus(property)]
+    async fn playback_status(&self) -> String {
+        self.target
+            .get(PLAYER_INTERFACE, "PlaybackStatus")
+            .await
+            .unwrap_or_else(|| "Stopped".to_string())
+    }
+
+    #[zbus(property)]
+    async fn loop_status(&self) -> String {
+        self.target
+            .get(PLAYER_INTERFACE, "LoopStatus")
+            .await
+            .unwrap_or_else(|| "None".to_string())
+    }
+
+    #[zbus(property)]
+    async fn set_loop_status(&self, loop_status: String) -> zbus::Result<()> {
+        self.target.set("LoopStatus", loop_status.into()).await
+    }
+
+    #[zbus(property)]
+    async fn rate(&self) -> f64 {
+        self.target.get(PLAYER_INTERFACE, "Rate").await.unwrap_or(1.0)
+    }
+
+    #[zbus(property)]
+    async fn set_rate(&self, rate: f64) -> zbus::Result<()> {
+        self.target.set("Rate", rate.into()).await
+    }
+
+    #[zbus(property)]
+    async fn shuffle(&self) -> bool {
+        self.target.get(PLAYER_INTERFACE, "Shuffle").await.unwrap_or(false)
+    }
+
+    #[zbus(property)]
+    async fn set_shuffle(&self, shuffle: bool) -> zbus::Result<()> {
+        self.target.set("Shuffle", shuffle.into()).await
+    }
+
+    #[zbus(property)]
+    async fn metadata(&self) -> HashMap<String, OwnedValue> {
+        self.target
+            .get(PLAYER_INTERFACE, "Metadata")
+            .await
+            .unwrap_or_default()
+    }
+
+    #[zbus(property)]
+    async fn volume(&self) -> f64 {
+        self.target.get(PLAYER_INTERFACE, "Volume").await.unwrap_or(0.0)
+    }
+
+    #[zbus(property)]
+    async fn set_volume(&self, volume: f64) -> zbus::Result<()> {
+        self.target.set("Volume", volume.into()).await
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn position(&self) -> i64 {
+        self.target.get(PLAYER_INTERFACE, "Position").await.unwrap_or(0)
+    }
+
+    #[zbus(property)]
+    async fn minimum_rate(&self) -> f64 {
+        self.target.get(PLAYER_INTERFACE, "MinimumRate").await.unwrap_or(1.0)
+    }
+
+    #[zbus(property)]
+    async fn maximum_rate(&self) -> f64 {
+        self.target.get(PLAYER_INTERFACE, "MaximumRate").await.unwrap_or(1.0)
+    }
+
+    #[zbus(property)]
+    async fn can_go_next(&self) -> bool {
+        self.target.get(PLAYER_INTERFACE, "CanGoNext").await.unwrap_or(false)
+    }
+
+    #[zbus(property)]
+    async fn can_go_previous(&self) -> bool {
+        self.target.get(PLAYER_INTERFACE, "CanGoPrevious").await.unwrap_or(false)
+    }
+
+    #[zbus(property)]
+    async fn can_play(&self) -> bool {
+        self.target.get(PLAYER_INTERFACE, "CanPlay").await.unwrap_or(false)
+    }
+
+    #[zbus(property)]
+    async fn can_pause(&self) -> bool {
+        self.target.get(PLAYER_INTERFACE, "CanPause").await.unwrap_or(false)
+    }
+
+    #[zbus(property)]
+    async fn can_seek(&self) -> bool {
+        self.target.get(PLAYER_INTERFACE, "CanSeek").await.unwrap_or(false)
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+
+    #[zbus(signal)]
+    async fn seeked(emitter: &SignalEmitter<'_>, position: i64) -> zbus::Result<()>;
+}
+
+pub fn subscription(state: Arc<Mutex<ServiceState>>) -> cosmic::iced::Subscription<Message> {
+    cosmic::iced::Subscription::run_with_id(
+        TypeId::of::<PlayerProxy>(),
+        cosmic::iced::stream::channel(1, move |_| async move {
+            if let Err(e) = serve(state).await {
+                eprintln!("Failed to publish the MPRIS proxy player: {}", e);
+            }
+            futures::future::pending::<()>().await;
+        }),
+    )
+}
+
+async fn serve(state: Arc<Mutex<ServiceState>>) -> zbus::Result<()> {
+    // Calls to the target go out over their own connection
+    let target = Target {
+        connection: Connection::session().await?,
+        state: state.clone(),
+    };
+    let connection = connection::Builder::session()?
+        .name(format!("org.mpris.MediaPlayer2.{}", PROXY_PLAYER_NAME))?
+        .serve_at(MPRIS_PATH, RootProxy { target: target.clone() })?
+        .serve_at(MPRIS_PATH, PlayerProxy { target: target.clone() })?
+        .build()
+        .await?;
+    let iface = connection
+        .object_server()
+        .interface::<_, PlayerProxy>(MPRIS_PATH)
+        .await?;
+
+    // Mirror the target's changes, and everything when the target itself changes
+    let mut mirrored = ServiceState::default();
+    let mut seeked: Option<SignalStream<'static>> = None;
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(STATE_POLL) => {}
+            Some(message) = next_signal(&mut seeked) => {
+                if let Ok(position) = message.body().deserialize::<i64>() {
+                    PlayerProxy::seeked(iface.signal_emitter(), position).await?;
+                }
+                continue;
+            }
+        }
+
+        let current = state.lock().map(|state| state.clone()).unwrap_or_default();
+        if current == mirrored {
+            continue;
+        }
+
+        let emitter = iface.signal_emitter();
+        let player = iface.get().await;
+        let new_target = current.bus_name != mirrored.bus_name;
+        if new_target || current.status != mirrored.status {
+            player.playback_status_changed(emitter).await?;
+        }
+        if new_target || current.title != mirrored.title || current.artist != mirrored.artist {
+            player.metadata_changed(emitter).await?;
+            player.can_go_next_changed(emitter).await?;
+            player.can_go_previous_changed(emitter).await?;
+            player.can_seek_changed(emitter).await?;
+        }
+        if new_target || current.volume != mirrored.volume {
+            player.volume_changed(emitter).await?;
+        }
+        if new_target {
+            // Positions jump on seeks, clients only learn about those from the signal
+            seeked = match target.proxy(PLAYER_INTERFACE).await {
+                Some(proxy) => proxy.receive_signal("Seeked").await.ok(),
+                None => None,
+            };
+            player.can_play_changed(emitter).await?;
+            player.can_pause_changed(emitter).await?;
+            player.loop_status_changed(emitter).await?;
+            player.shuffle_changed(emitter).await?;
+            player.rate_changed(emitter).await?;
+        }
+
+        mirrored = current;
+    }
+}
+
+/// The next signal on `stream`, never resolving while there is none
+async fn next_signal(stream: &mut Option<SignalStream<'static>>) -> Option<zbus::Message> {
+    match stream {
+        Some(stream) => stream.next().await,
+        None => futures::future::pending().await,
+    }
+}
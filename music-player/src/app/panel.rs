@@ -0,0 +1,416 @@
+use crate::config::{PanelAction, PanelGesture};
+use crate::fl;
+use cosmic::iced::mouse::ScrollDelta;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// Touchpad (pixel) deltas are converted to wheel lines with this factor
+const PIXELS_PER_LINE: f32 = 20.0;
+/// A pause this long between scroll events ends a gesture
+const GESTURE_GAP: Duration = Duration::from_millis(300);
+/// How long a left click waits for a second one, a bit longer than the toolkit's
+/// double-click interval
+pub const DOUBLE_CLICK_WINDOW: Duration = Duration::from_millis(400);
+
+/// How much a volume action changes the volume
+pub const VOLUME_STEP: f64 = 0.05;
+/// How far a seek action jumps, in microseconds
+pub const SEEK_STEP: i64 = 10_000_000;
+
+/// Blank space between the end and the start of scrolling panel text
+const MARQUEE_GAP: &str = "   ·   ";
+
+/// Actions offered for each gesture, in the order of `PANEL_ACTION_LABELS`
+pub const PANEL_ACTIONS: [PanelAction; 12] = [
+    PanelAction::None,
+    PanelAction::TogglePopup,
+    PanelAction::PlayPause,
+    PanelAction::Next,
+    PanelAction::Previous,
+    PanelAction::VolumeUp,
+    PanelAction::VolumeDown,
+    PanelAction::SeekForward,
+    PanelAction::SeekBackward,
+    PanelAction::RaisePlayer,
+    PanelAction::NextPlayer,
+    PanelAction::PreviousPlayer,
+];
+
+pub static PANEL_ACTION_LABELS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    vec![
+        fl!("action-none"),
+        fl!("action-toggle-popup"),
+        fl!("action-play-pause"),
+        fl!("action-next"),
+        fl!("action-previous"),
+        fl!("action-volume-up"),
+        fl!("action-volume-down"),
+        fl!("action-seek-forward"),
+        fl!("action-seek-backward"),
+        fl!("action-raise-player"),
+        fl!("action-next-player"),
+        fl!("action-previous-player"),
+    ]
+});
+
+pub const PANEL_GESTURES: [PanelGesture; 8] = [
+    PanelGesture::LeftClick,
+    PanelGesture::MiddleClick,
+    PanelGesture::RightClick,
+    PanelGesture::DoubleClick,
+    PanelGesture::ScrollUp,
+    PanelGesture::ScrollDown,
+    PanelGesture::ScrollLeft,
+    PanelGesture::ScrollRight,
+];
+
+/// Name of a gesture in the panel button settings
+pub fn gesture_label(gesture: PanelGesture) -> String {
+    match gesture {
+        PanelGesture::LeftClick => fl!("gesture-left-click"),
+        PanelGesture::MiddleClick => fl!("gesture-middle-click"),
+        PanelGesture::RightClick => fl!("gesture-right-click"),
+        PanelGesture::DoubleClick => fl!("gesture-double-click"),
+        PanelGesture::ScrollUp => fl!("gesture-scroll-up"),
+        PanelGesture::ScrollDown => fl!("gesture-scroll-down"),
+        PanelGesture::ScrollLeft => fl!("gesture-scroll-left"),
+        PanelGesture::ScrollRight => fl!("gesture-scroll-right"),
+    }
+}
+
+/// Collects scroll deltas until they add up to a step
+///
+/// Every wheel notch can trigger an action, but a touchpad gesture triggers at most one.
+#[derive(Debug, Default)]
+pub struct ScrollAccumulator {
+    x: f32,
+    y: f32,
+    last_event: Option<Instant>,
+    fired: bool,
+}
+
+impl ScrollAccumulator {
+    /// Adds a scroll event, returning the gesture once `threshold` lines have been scrolled
+    pub fn push(
+        &mut self,
+        delta: ScrollDelta,
+        threshold: f32,
+        now: Instant,
+    ) -> Option<PanelGesture> {
+        if self
+            .last_event
+            .map_or(true, |last| now.duration_since(last) > GESTURE_GAP)
+        {
+            *self = Self::default();
+        }
+        self.last_event = Some(now);
+
+        let (x, y, continuous) = match delta {
+            ScrollDelta::Lines { x, y } => (x, y, false),
+            ScrollDelta::Pixels { x, y } => (x / PIXELS_PER_LINE, y / PIXELS_PER_LINE, true),
+        };
+        if continuous && self.fired {
+            return None;
+        }
+        self.x += x;
+        self.y += y;
+
+        let gesture = if self.y.abs() >= threshold && self.y.abs() >= self.x.abs() {
+            if self.y > 0.0 {
+                PanelGesture::ScrollUp
+            } else {
+                PanelGesture::ScrollDown
+            }
+        } else if self.x.abs() >= threshold {
+            if self.x > 0.0 {
+                PanelGesture::ScrollLeft
+            } else {
+                PanelGesture::ScrollRight
+            }
+        } else {
+            return None;
+        };
+
+        self.x = 0.0;
+        self.y = 0.0;
+        self.fired = continuous;
+        Some(gesture)
+    }
+}
+
+/// What a left click on the panel button turned out to be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Click {
+    /// Possibly a single click, pass the id to `ClickTracker::expire` once
+    /// `DOUBLE_CLICK_WINDOW` has passed
+    Pending(u64),
+    /// The second click of a double-click
+    Double,
+}
+
+/// Holds back left clicks while they may still turn into a double-click
+///
+/// A second click before the first one expired makes a double-click, and neither of its
+/// clicks counts as a left click.
+#[derive(Debug, Default)]
+pub struct ClickTracker {
+    /// Left click waiting for `DOUBLE_CLICK_WINDOW` to pass
+    pending: Option<u64>,
+    next_id: u64,
+}
+
+impl ClickTracker {
+    pub fn press(&mut self) -> Click {
+        if self.pending.take().is_some() {
+            return Click::Double;
+        }
+        self.next_id += 1;
+        self.pending = Some(self.next_id);
+        Click::Pending(self.next_id)
+    }
+
+    /// Whether left click `id` stayed a single click
+    pub fn expire(&mut self, id: u64) -> bool {
+        if self.pending == Some(id) {
+            self.pending = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// "Artist – Title", or just the title when the artist is unknown
+pub fn format_now_playing(artist: &str, title: &str) -> String {
+    if artist.is_empty() {
+        title.to_string()
+    } else {
+        format!("{} – {}", artist, title)
+    }
+}
+
+/// Cuts `text` to at most `max_chars` characters, ending in an ellipsis
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    // Not even the ellipsis fits
+    if max_chars == 0 {
+        return String::new();
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// The `max_chars` wide window of `text` scrolled by `offset` characters, wrapping around
+pub fn marquee(text: &str, max_chars: usize, offset: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let looped: Vec<char> = text.chars().chain(MARQUEE_GAP.chars()).collect();
+    looped
+        .iter()
+        .cycle()
+        .skip(offset % looped.len())
+        .take(max_chars)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(x: f32, y: f32) -> ScrollDelta {
+        ScrollDelta::Lines { x, y }
+    }
+
+    fn pixels(x: f32, y: f32) -> ScrollDelta {
+        ScrollDelta::Pixels { x, y }
+    }
+
+    #[test]
+    fn every_wheel_notch_triggers() {
+        let mut scroll = ScrollAccumulator::default();
+        let now = Instant::now();
+        assert_eq!(
+            scroll.push(lines(0.0, 1.0), 1.0, now),
+            Some(PanelGesture::ScrollUp)
+        );
+        assert_eq!(
+            scroll.push(lines(0.0, 1.0), 1.0, now),
+            Some(PanelGesture::ScrollUp)
+        );
+        assert_eq!(
+            scroll.push(lines(0.0, -1.0), 1.0, now),
+            Some(PanelGesture::ScrollDown)
+        );
+    }
+
+    #[test]
+    fn wheel_notches_add_up_to_the_threshold() {
+        let mut scroll = ScrollAccumulator::default();
+        let now = Instant::now();
+        assert_eq!(scroll.push(lines(0.0, 1.0), 2.0, now), None);
+        assert_eq!(
+            scroll.push(lines(0.0, 1.0), 2.0, now),
+            Some(PanelGesture::ScrollUp)
+        );
+    }
+
+    #[test]
+    fn touchpad_gesture_triggers_once() {
+        let mut scroll = ScrollAccumulator::default();
+        let now = Instant::now();
+        assert_eq!(scroll.push(pixels(0.0, -10.0), 1.0, now), None);
+        assert_eq!(
+            scroll.push(pixels(0.0, -10.0), 1.0, now),
+            Some(PanelGesture::ScrollDown)
+        );
+        assert_eq!(scroll.push(pixels(0.0, -40.0), 1.0, now), None);
+
+        // A pause starts a new gesture
+        let later = now + GESTURE_GAP * 2;
+        assert_eq!(
+            scroll.push(pixels(0.0, -40.0), 1.0, later),
+            Some(PanelGesture::ScrollDown)
+        );
+    }
+
+    #[test]
+    fn pause_discards_partial_scrolling() {
+        let mut scroll = ScrollAccumulator::default();
+        let now = Instant::now();
+        assert_eq!(scroll.push(pixels(0.0, 15.0), 1.0, now), None);
+        assert_eq!(
+            scroll.push(pixels(0.0, 15.0), 1.0, now + GESTURE_GAP * 2),
+            None
+        );
+    }
+
+    #[test]
+    fn dominant_axis_decides_the_direction() {
+        let mut scroll = ScrollAccumulator::default();
+        let now = Instant::now();
+        assert_eq!(
+            scroll.push(lines(1.0, 0.0), 1.0, now),
+            Some(PanelGesture::ScrollLeft)
+        );
+        assert_eq!(
+            scroll.push(lines(-2.0, 1.0), 1.0, now),
+            Some(PanelGesture::ScrollRight)
+        );
+        assert_eq!(
+            scroll.push(lines(1.0, 1.0), 1.0, now),
+            Some(PanelGesture::ScrollUp)
+        );
+    }
+
+    fn pending(click: Click) -> u64 {
+        match click {
+            Click::Pending(id) => id,
+            Click::Double => panic!("expected a pending click"),
+        }
+    }
+
+    #[test]
+    fn single_click_acts_once_the_window_passed() {
+        let mut clicks = ClickTracker::default();
+        let id = pending(clicks.press());
+        assert!(clicks.expire(id));
+        assert!(!clicks.expire(id));
+    }
+
+    #[test]
+    fn second_click_within_the_window_is_a_double_click() {
+        let mut clicks = ClickTracker::default();
+        let id = pending(clicks.press());
+        assert_eq!(clicks.press(), Click::Double);
+        // The first click no longer acts on its own
+        assert!(!clicks.expire(id));
+
+        // Clicks after that start over
+        let id = pending(clicks.press());
+        assert!(clicks.expire(id));
+    }
+
+    #[test]
+    fn clicks_further_apart_than_the_window_are_single_clicks() {
+        let mut clicks = ClickTracker::default();
+        let first = pending(clicks.press());
+        assert!(clicks.expire(first));
+        let second = pending(clicks.press());
+        assert_ne!(first, second);
+        assert!(clicks.expire(second));
+    }
+
+    #[test]
+    fn triple_click_is_a_double_click_then_a_pending_click() {
+        let mut clicks = ClickTracker::default();
+        let first = pending(clicks.press());
+        assert_eq!(clicks.press(), Click::Double);
+        let third = pending(clicks.press());
+        assert!(!clicks.expire(first));
+        assert!(clicks.expire(third));
+    }
+
+    #[test]
+    fn truncate_keeps_text_that_fits() {
+        assert_eq!(truncate("Song", 4), "Song");
+        assert_eq!(truncate("", 0), "");
+    }
+
+    #[test]
+    fn truncate_ends_in_an_ellipsis() {
+        assert_eq!(truncate("Long song title", 8), "Long so…");
+        assert_eq!(truncate("Long song title", 8).chars().count(), 8);
+    }
+
+    #[test]
+    fn truncate_counts_characters_not_bytes() {
+        assert_eq!(truncate("Björk – Jóga", 12), "Björk – Jóga");
+        assert_eq!(truncate("Björk – Jóga", 7), "Björk …");
+        assert_eq!(
+            truncate("坂本龍一 – 戦場のメリークリスマス", 5),
+            "坂本龍一…"
+        );
+    }
+
+    #[test]
+    fn truncate_to_zero_or_one_character() {
+        assert_eq!(truncate("Song", 0), "");
+        assert_eq!(truncate("Song", 1), "…");
+    }
+
+    #[test]
+    fn marquee_keeps_text_that_fits() {
+        assert_eq!(marquee("Song", 10, 3), "Song");
+    }
+
+    #[test]
+    fn marquee_scrolls_by_offset() {
+        assert_eq!(marquee("abcdefgh", 4, 0), "abcd");
+        assert_eq!(marquee("abcdefgh", 4, 2), "cdef");
+        assert_eq!(marquee("abcdefgh", 4, 6), "gh  ");
+    }
+
+    #[test]
+    fn marquee_wraps_around_after_the_gap() {
+        let cycle = "abcdefgh".chars().count() + MARQUEE_GAP.chars().count();
+        assert_eq!(marquee("abcdefgh", 4, cycle), "abcd");
+        assert_eq!(marquee("abcdefgh", 4, cycle + 2), "cdef");
+        assert_eq!(marquee("abcdefgh", 4, cycle - 1), " abc");
+    }
+
+    #[test]
+    fn marquee_counts_characters_not_bytes() {
+        assert_eq!(marquee("Jóga – Björk", 4, 1), "óga ");
+        assert_eq!(marquee("戦場のメリークリスマス", 3, 2), "のメリ");
+    }
+
+    #[test]
+    fn marquee_of_zero_or_one_character() {
+        assert_eq!(marquee("Song", 0, 2), "");
+        assert_eq!(marquee("Song", 1, 2), "n");
+    }
+}
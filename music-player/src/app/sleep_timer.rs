@@ -0,0 +1,162 @@
+use super::fade::FadeTarget;
+use crate::music::TrackProgress;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Volume is lowered gradually during this final stretch before pausing
+pub const SLEEP_FADE: Duration = Duration::from_secs(60);
+
+/// How often the timer is updated
+pub const SLEEP_TIMER_TICK: Duration = Duration::from_secs(1);
+
+/// Preset lengths offered in the popup, in minutes
+pub const SLEEP_TIMER_PRESETS: [u32; 3] = [15, 30, 60];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepTimerLength {
+    Minutes(u32),
+    EndOfTrack,
+}
+
+#[derive(Debug, Clone)]
+pub enum SleepTimerEnd {
+    At(Instant),
+    /// Waits for the track playing when the timer was set, `None` until it is known
+    EndOfTrack {
+        track: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct SleepTimer {
+    pub end: SleepTimerEnd,
+    /// Players paused when the timer runs out, the first one decides "end of track"
+    pub targets: Vec<FadeTarget>,
+    /// Volumes from before the final fade, restored after pausing
+    pub original_volumes: HashMap<FadeTarget, f64>,
+    /// Time left as of the last tick, `None` while unknown
+    pub remaining: Option<Duration>,
+}
+
+impl SleepTimer {
+    pub fn new(length: SleepTimerLength, targets: Vec<FadeTarget>) -> Self {
+        let end = match length {
+            SleepTimerLength::Minutes(minutes) => {
+                SleepTimerEnd::At(Instant::now() + Duration::from_secs(u64::from(minutes) * 60))
+            }
+            SleepTimerLength::EndOfTrack => SleepTimerEnd::EndOfTrack { track: None },
+        };
+
+        Self {
+            end,
+            targets,
+            original_volumes: HashMap::new(),
+            remaining: None,
+        }
+    }
+
+    /// Updates the remaining time; `progress` is the current track of the first target,
+    /// only used when waiting for the end of the track
+    pub fn update(&mut self, progress: Option<TrackProgress>) {
+        self.remaining = match self.end {
+            SleepTimerEnd::At(deadline) => Some(deadline.saturating_duration_since(Instant::now())),
+            SleepTimerEnd::EndOfTrack { ref mut track } => progress.map(|progress| {
+                let waited_for = track.get_or_insert_with(|| progress.track.clone());
+                // Ends before the next tick, or another track already started
+                if progress.remaining <= SLEEP_TIMER_TICK || *waited_for != progress.track {
+                    Duration::ZERO
+                } else {
+                    progress.remaining
+                }
+            }),
+        };
+    }
+
+    /// Fraction of the original volume to play at, below 1.0 during the final fade
+    pub fn volume_factor(&self) -> f64 {
+        match self.remaining {
+            Some(remaining) if remaining < SLEEP_FADE => {
+                remaining.as_secs_f64() / SLEEP_FADE.as_secs_f64()
+            }
+            _ => 1.0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining == Some(Duration::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(track: &str, remaining_secs: u64) -> Option<TrackProgress> {
+        Some(TrackProgress {
+            track: track.to_string(),
+            remaining: Duration::from_secs(remaining_secs),
+        })
+    }
+
+    fn end_of_track() -> SleepTimer {
+        SleepTimer::new(SleepTimerLength::EndOfTrack, vec![FadeTarget::Selected])
+    }
+
+    #[test]
+    fn finishes_on_the_last_tick_of_the_track() {
+        let mut timer = end_of_track();
+        timer.update(progress("a", 3));
+        assert!(!timer.is_finished());
+        timer.update(progress("a", 2));
+        assert!(!timer.is_finished());
+        timer.update(progress("a", 1));
+        assert!(timer.is_finished());
+    }
+
+    #[test]
+    fn finishes_when_another_track_started() {
+        let mut timer = end_of_track();
+        timer.update(progress("a", 30));
+        timer.update(progress("b", 200));
+        assert!(timer.is_finished());
+    }
+
+    #[test]
+    fn seeking_back_keeps_waiting() {
+        let mut timer = end_of_track();
+        timer.update(progress("a", 30));
+        timer.update(progress("a", 120));
+        assert!(!timer.is_finished());
+        assert_eq!(timer.remaining, Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn unknown_position_keeps_waiting() {
+        let mut timer = end_of_track();
+        timer.update(None);
+        assert!(!timer.is_finished());
+        assert_eq!(timer.volume_factor(), 1.0);
+    }
+
+    #[test]
+    fn fades_out_by_the_end_of_the_track() {
+        let mut timer = end_of_track();
+        timer.update(progress("a", 120));
+        assert_eq!(timer.volume_factor(), 1.0);
+        timer.update(progress("a", 30));
+        assert_eq!(timer.volume_factor(), 0.5);
+        timer.update(progress("a", 1));
+        assert_eq!(timer.volume_factor(), 0.0);
+    }
+
+    #[test]
+    fn minutes_count_down_from_now() {
+        let mut timer = SleepTimer::new(SleepTimerLength::Minutes(15), vec![FadeTarget::Selected]);
+        timer.update(None);
+        let remaining = timer.remaining.unwrap();
+        assert!(remaining <= Duration::from_secs(15 * 60));
+        assert!(remaining > Duration::from_secs(15 * 60 - 5));
+        assert_eq!(timer.volume_factor(), 1.0);
+        assert!(!timer.is_finished());
+    }
+}
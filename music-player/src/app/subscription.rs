@@ -1,7 +1,146 @@
-use super::Message;
+use super::sleep_timer::SLEEP_TIMER_TICK;
+use super::{CosmicAppletMusic, Message};
+use crate::config::AppConfig;
+use crate::music::PROXY_PLAYER_NAME;
 use cosmic::iced::time;
+use cosmic::Application;
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt};
+use mpris::PlaybackStatus;
+use std::any::TypeId;
 use std::time::Duration;
+use tokio::signal::unix::{signal, SignalKind};
 
-pub fn subscription() -> cosmic::iced::Subscription<Message> {
-    time::every(Duration::from_millis(500)).map(|_| Message::FindPlayer)
+/// Bus name prefix of MPRIS players
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+/// Polling interval while no player exists, only to notice new ones
+const SUSPENDED_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+pub fn subscription(app: &CosmicAppletMusic) -> cosmic::iced::Subscription<Message> {
+    let mut subscriptions = vec![
+        time::every(refresh_interval(app)).map(|_| Message::FindPlayer),
+        // Alarms are checked well within each minute so none is skipped
+        time::every(Duration::from_secs(15)).map(|_| Message::CheckAlarms),
+        // Pick up settings changed by another instance or by hand
+        app.core
+            .watch_config::<AppConfig>(CosmicAppletMusic::APP_ID)
+            .map(|update| Message::ConfigChanged(update.config)),
+        super::dbus_service::subscription(app.service_state.clone()),
+        super::mpris_proxy::subscription(app.service_state.clone()),
+        shutdown_signals(),
+        mpris_name_changes(),
+    ];
+
+    // Only redraw the visualizer while something is being captured
+    if app.visualizer.is_capturing() {
+        subscriptions.push(time::every(Duration::from_millis(33)).map(|_| Message::VisualizerTick));
+    }
+
+    // Shortcuts only apply while the popup is open
+    if app.popup.is_some() {
+        subscriptions.push(cosmic::iced::keyboard::on_key_press(super::keyboard::shortcut));
+    }
+
+    // Scroll panel text that is too long to fit
+    if app.panel_text_scrolls() {
+        subscriptions.push(time::every(Duration::from_millis(300)).map(|_| Message::MarqueeTick));
+    }
+
+    // The sleep timer keeps running while the popup is closed
+    if app.sleep_timer.is_some() {
+        subscriptions.push(time::every(SLEEP_TIMER_TICK).map(|_| Message::SleepTimerTick));
+    }
+
+    cosmic::iced::Subscription::batch(subscriptions)
+}
+
+/// Turns SIGTERM (sent by the panel) and SIGINT into a clean shutdown
+fn shutdown_signals() -> cosmic::iced::Subscription<Message> {
+    struct ShutdownSignals;
+
+    cosmic::iced::Subscription::run_with_id(
+        TypeId::of::<ShutdownSignals>(),
+        cosmic::iced::stream::channel(1, |mut output| async move {
+            let terminate = async {
+                match signal(SignalKind::terminate()) {
+                    Ok(mut terminate) => {
+                        terminate.recv().await;
+                    }
+                    Err(_) => futures::future::pending::<()>().await,
+                }
+            };
+            tokio::select! {
+                _ = terminate => {}
+                _ = tokio::signal::ctrl_c() => {}
+            }
+            let _ = output.send(Message::Shutdown).await;
+            futures::future::pending::<()>().await;
+        }),
+    )
+}
+
+/// Rediscovers players whenever an MPRIS player appears or goes away
+fn mpris_name_changes() -> cosmic::iced::Subscription<Message> {
+    struct MprisNameChanges;
+
+    cosmic::iced::Subscription::run_with_id(
+        TypeId::of::<MprisNameChanges>(),
+        cosmic::iced::stream::channel(1, |mut output| async move {
+            if let Err(e) = watch_mpris_names(&mut output).await {
+                eprintln!("Failed to watch for MPRIS players: {}", e);
+            }
+            futures::future::pending::<()>().await;
+        }),
+    )
+}
+
+async fn watch_mpris_names(output: &mut mpsc::Sender<Message>) -> zbus::Result<()> {
+    let connection = zbus::Connection::session().await?;
+    let dbus = zbus::fdo::DBusProxy::new(&connection).await?;
+    let mut changes = dbus.receive_name_owner_changed().await?;
+    let own_name = format!("{}{}", MPRIS_PREFIX, PROXY_PLAYER_NAME);
+
+    while let Some(change) = changes.next().await {
+        let Ok(args) = change.args() else {
+            continue;
+        };
+        let name = args.name().as_str();
+        if name.starts_with(MPRIS_PREFIX) && name != own_name {
+            let _ = output.send(Message::DiscoverPlayers).await;
+        }
+    }
+    Ok(())
+}
+
+/// Polls fast while the popup is open or something plays, slowly while idle and
+/// hardly at all while there is no player
+fn refresh_interval(app: &CosmicAppletMusic) -> Duration {
+    let (active_ms, idle_ms) = app
+        .config_manager
+        .as_ref()
+        .map(|config| {
+            (
+                config.get_refresh_interval_ms(),
+                config.get_idle_refresh_interval_ms(),
+            )
+        })
+        .unwrap_or((500, 3000));
+
+    let playing = app.player_info.status == PlaybackStatus::Playing
+        || app
+            .all_players_info
+            .iter()
+            .any(|player| player.status == PlaybackStatus::Playing);
+    let no_players = app.player_info.identity.is_empty()
+        && app.all_players_info.is_empty()
+        && app.music_controller.get_discovered_players().is_empty();
+
+    if app.popup.is_some() || playing {
+        Duration::from_millis(u64::from(active_ms))
+    } else if no_players {
+        SUSPENDED_REFRESH_INTERVAL
+    } else {
+        Duration::from_millis(u64::from(idle_ms))
+    }
 }
@@ -1,4 +1,7 @@
-use crate::app::{CosmicAppletMusic, Message};
+use crate::app::{panel, CosmicAppletMusic, Message};
+use crate::config::PanelGesture;
+use crate::music::PlayerInfo;
+use crate::{fl, i18n};
 use cosmic::widget::Id;
 use cosmic::Element;
 use mpris::PlaybackStatus;
@@ -30,12 +33,20 @@ pub fn view(app: &CosmicAppletMusic) -> Element<'_, Message> {
         .map(|config| config.get_show_all_players())
         .unwrap_or(false);
 
+    // The profile icon of the player the panel stands for goes next to the status icon
+    let profile_icon = app.panel_player().and_then(|player| {
+        app.config_manager
+            .as_ref()
+            .and_then(|config| config.get_player_profile(&player.identity))
+            .and_then(|profile| profile.icon_name.as_deref())
+    });
+
     let icon = if show_all_players {
         // In multi-player mode, check if ANY player is playing
         let any_playing = app
             .all_players_info
             .iter()
-            .any(|p| p.status == PlaybackStatus::Playing);
+            .any(|p| p.status == PlaybackStatus::Playing && app.is_player_visible(&p.identity));
 
         if any_playing {
             AppIcon::Paused // Show pause when any player is playing
@@ -51,33 +62,102 @@ pub fn view(app: &CosmicAppletMusic) -> Element<'_, Message> {
         }
     };
 
-    use cosmic::iced::mouse;
-
-    cosmic::widget::autosize::autosize(
-        cosmic::widget::mouse_area(
-            app.core
-                .applet
-                .icon_button(icon.to_str())
-                .on_press_down(Message::TogglePopup),
-        )
-        .on_scroll(|delta| match delta {
-            mouse::ScrollDelta::Lines { y, .. } => {
-                if y > 0.0 {
-                    Message::ScrollUp
-                } else {
-                    Message::ScrollDown
-                }
-            }
-            mouse::ScrollDelta::Pixels { y, .. } => {
-                if y > 0.0 {
-                    Message::ScrollUp
-                } else {
-                    Message::ScrollDown
-                }
-            }
-        })
-        .on_middle_press(Message::MiddleClick),
-        AUTOSIZE_MAIN_ID.clone(),
+    let panel_text = view_panel_text(app);
+    let button = if profile_icon.is_none() && panel_text.is_none() {
+        app.core.applet.icon_button(icon.to_str())
+    } else {
+        let (icon_size, _) = app.core.applet.suggested_size(true);
+        let mut children: Vec<Element<'_, Message>> = Vec::new();
+        if let Some(profile_icon) = profile_icon {
+            children.push(cosmic::widget::icon::from_name(profile_icon).size(icon_size).into());
+        }
+        children.push(cosmic::widget::icon::from_name(icon.to_str()).size(icon_size).into());
+        children.extend(panel_text);
+
+        let content: Element<'_, Message> = if app.core.applet.is_horizontal() {
+            directional_row(children)
+                .spacing(4.0)
+                .align_y(cosmic::iced::Alignment::Center)
+                .into()
+        } else {
+            cosmic::widget::column::with_children(children)
+                .spacing(4.0)
+                .align_x(cosmic::iced::Alignment::Center)
+                .into()
+        };
+
+        cosmic::widget::button::custom(content)
+            .class(cosmic::theme::Button::AppletIcon)
+            .padding([4, 8])
+    };
+
+    // Gestures are mapped to actions in `handle_panel_gesture`, which also tells double
+    // clicks apart from left clicks
+    let panel_button = cosmic::widget::mouse_area(
+        button.on_press_down(Message::PanelGesture(PanelGesture::LeftClick)),
     )
-    .into()
+    .on_scroll(Message::PanelScrolled)
+    .on_middle_press(Message::PanelGesture(PanelGesture::MiddleClick))
+    .on_right_press(Message::PanelGesture(PanelGesture::RightClick));
+
+    cosmic::widget::autosize::autosize(panel_button, AUTOSIZE_MAIN_ID.clone()).into()
+}
+
+/// Now-playing text next to the icon, cut to the configured width or scrolled
+fn view_panel_text(app: &CosmicAppletMusic) -> Option<Element<'_, Message>> {
+    let text = app.panel_text()?;
+    let config = app.config_manager.as_ref()?;
+    let max_chars = config.get_panel_text_max_chars() as usize;
+
+    let text = if config.get_panel_text_marquee() {
+        panel::marquee(&text, max_chars, app.marquee_offset)
+    } else {
+        panel::truncate(&text, max_chars)
+    };
+
+    if app.core.applet.is_horizontal() {
+        return Some(app.core.applet.text(text).into());
+    }
+
+    // Vertical panels get one character per line
+    let stacked = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .fold(cosmic::widget::column(), |column, c| {
+            column.push(app.core.applet.text(c.to_string()))
+        })
+        .align_x(cosmic::iced::Alignment::Center);
+    Some(stacked.into())
+}
+
+/// A row whose children are laid out right to left for right-to-left languages
+pub fn directional_row<'a>(
+    mut children: Vec<Element<'a, Message>>,
+) -> cosmic::widget::Row<'a, Message> {
+    if i18n::is_rtl() {
+        children.reverse();
+    }
+    cosmic::widget::row::with_children(children)
+}
+
+/// Title of `player`, or a placeholder when there is no player or it reports no title
+pub fn display_title(player: &PlayerInfo) -> String {
+    if player.identity.is_empty() {
+        fl!("no-music-playing")
+    } else if player.title.is_empty() {
+        fl!("unknown-title")
+    } else {
+        player.title.clone()
+    }
+}
+
+/// Artist of `player`, or a placeholder when it reports none
+pub fn display_artist(player: &PlayerInfo) -> String {
+    if player.identity.is_empty() {
+        String::new()
+    } else if player.artist.is_empty() {
+        fl!("unknown-artist")
+    } else {
+        player.artist.clone()
+    }
 }
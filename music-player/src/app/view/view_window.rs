@@ -1,4 +1,13 @@
+use crate::app::alarm;
+use crate::app::panel::{self, PANEL_ACTIONS, PANEL_ACTION_LABELS, PANEL_GESTURES};
+use crate::app::sleep_timer::{SleepTimerLength, SLEEP_TIMER_PRESETS};
+use crate::app::view::{directional_row, display_artist, display_title};
 use crate::app::{CosmicAppletMusic, Message, PopupTab};
+use crate::art::AlbumArt;
+use crate::config::{
+    PlayerProfile, VerticalPanelText, DUCKING_AMOUNT_RANGE, MAX_VOLUME_OPTIONS,
+};
+use crate::fl;
 use cosmic::{theme, Element};
 use mpris::PlaybackStatus;
 
@@ -8,32 +17,29 @@ pub fn view_window(app: &CosmicAppletMusic, _id: cosmic::iced::window::Id) -> El
     } = theme::active().cosmic().spacing;
 
     // Tab bar with proper alignment
-    let controls_button = cosmic::widget::button::text(if app.active_tab == PopupTab::Controls {
-        "● Controls"
-    } else {
-        "○ Controls"
-    })
-    .on_press(Message::SwitchTab(PopupTab::Controls));
+    let tab_button = |tab: PopupTab, label: String| {
+        let marker = if app.active_tab == tab { "●" } else { "○" };
+        cosmic::widget::button::text(format!("{} {}", marker, label))
+            .on_press(Message::SwitchTab(tab))
+    };
+    let controls_button = tab_button(PopupTab::Controls, fl!("controls"));
+    let alarms_button = tab_button(PopupTab::Alarms, fl!("alarms"));
+    let settings_button = tab_button(PopupTab::Settings, fl!("settings"));
 
-    let settings_button = cosmic::widget::button::text(if app.active_tab == PopupTab::Settings {
-        "● Settings"
-    } else {
-        "○ Settings"
-    })
-    .on_press(Message::SwitchTab(PopupTab::Settings));
-
-    let tabs = cosmic::widget::row()
-        .width(cosmic::iced::Length::Fill)
-        .push(controls_button)
-        .push(
-            cosmic::widget::container(cosmic::widget::horizontal_space())
-                .width(cosmic::iced::Length::Fill),
-        )
-        .push(settings_button);
+    let tabs = directional_row(vec![
+        controls_button.into(),
+        cosmic::widget::container(cosmic::widget::horizontal_space())
+            .width(cosmic::iced::Length::Fill)
+            .into(),
+        alarms_button.into(),
+        settings_button.into(),
+    ])
+    .width(cosmic::iced::Length::Fill);
 
     // Tab content
     let tab_content = match app.active_tab {
         PopupTab::Controls => view_controls_tab(app, space_s.into(), space_m.into()),
+        PopupTab::Alarms => view_alarms_tab(app, space_s.into(), space_m.into()),
         PopupTab::Settings => view_settings_tab(app, space_s.into(), space_m.into()),
     };
 
@@ -58,6 +64,270 @@ pub fn view_window(app: &CosmicAppletMusic, _id: cosmic::iced::window::Id) -> El
 }
 
 fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Element<'_, Message> {
+    let player_controls = view_player_controls(app, space_s, space_m);
+
+    let mut content = cosmic::widget::column().spacing(space_m);
+
+    // System output controls on top, unless the user prefers the stock audio applet
+    if let Some(system_volume) = view_system_volume(app, space_s) {
+        content = content
+            .push(system_volume)
+            .push(cosmic::widget::divider::horizontal::default());
+    }
+
+    content
+        .push(player_controls)
+        .push(cosmic::widget::divider::horizontal::default())
+        .push(view_sleep_timer(app, space_s))
+        .into()
+}
+
+fn view_sleep_timer(app: &CosmicAppletMusic, space_s: f32) -> Element<'_, Message> {
+    if let Some(ref timer) = app.sleep_timer {
+        let status = match timer.remaining {
+            Some(remaining) => {
+                let seconds = remaining.as_secs();
+                fl!(
+                    "pausing-in",
+                    time = format!("{}:{:02}", seconds / 60, seconds % 60)
+                )
+            }
+            None => fl!("pausing-at-track-end"),
+        };
+
+        return directional_row(vec![
+            cosmic::widget::icon::from_name("alarm-symbolic").size(16).into(),
+            cosmic::widget::text::body(status).into(),
+            cosmic::widget::horizontal_space().into(),
+            cosmic::widget::button::text(fl!("cancel"))
+                .on_press(Message::CancelSleepTimer)
+                .into(),
+        ])
+        .spacing(space_s)
+        .align_y(cosmic::iced::Alignment::Center)
+        .into();
+    }
+
+    let presets = SLEEP_TIMER_PRESETS.iter().fold(
+        cosmic::widget::row().spacing(space_s / 2.0),
+        |row, minutes| {
+            row.push(
+                cosmic::widget::button::text(fl!("sleep-timer-preset", minutes = *minutes))
+                    .on_press(Message::SetSleepTimer(SleepTimerLength::Minutes(*minutes))),
+            )
+        },
+    );
+
+    let custom = directional_row(vec![
+        cosmic::widget::button::text(fl!("end-of-track"))
+            .on_press(Message::SetSleepTimer(SleepTimerLength::EndOfTrack))
+            .into(),
+        cosmic::widget::horizontal_space().into(),
+        cosmic::widget::text_input(fl!("minutes"), &app.sleep_timer_custom_minutes)
+            .on_input(Message::SleepTimerCustomChanged)
+            .width(cosmic::iced::Length::Fixed(80.0))
+            .into(),
+        cosmic::widget::button::text(fl!("set"))
+            .on_press(Message::SetCustomSleepTimer)
+            .into(),
+    ])
+    .spacing(space_s / 2.0)
+    .align_y(cosmic::iced::Alignment::Center);
+
+    cosmic::widget::column()
+        .spacing(space_s / 2.0)
+        .push(cosmic::widget::text::caption(fl!("sleep-timer")))
+        .push(presets)
+        .push(custom)
+        .into()
+}
+
+fn view_alarms_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Element<'_, Message> {
+    let mut content = cosmic::widget::column().spacing(space_m);
+
+    // Saved alarms
+    let alarms = app
+        .config_manager
+        .as_ref()
+        .map(|config| config.get_alarms())
+        .unwrap_or_default();
+
+    if alarms.is_empty() {
+        content = content.push(cosmic::widget::text::caption(fl!("no-alarms")));
+    }
+
+    for (index, alarm) in alarms.iter().enumerate() {
+        let player = alarm
+            .player
+            .as_deref()
+            .map(|identity| display_name(app, identity).to_string())
+            .unwrap_or_else(|| fl!("selected-player"));
+
+        let details = cosmic::widget::column()
+            .push(cosmic::widget::text::body(alarm::describe(alarm)))
+            .push(cosmic::widget::text::caption(match alarm.uri {
+                Some(ref uri) => format!("{} · {}", player, uri),
+                None => player,
+            }))
+            .width(cosmic::iced::Length::Fill);
+
+        content = content.push(
+            directional_row(vec![
+                cosmic::widget::checkbox("", alarm.enabled)
+                    .on_toggle(move |enabled| Message::ToggleAlarm(index, enabled))
+                    .into(),
+                details.into(),
+                cosmic::widget::button::icon(cosmic::widget::icon::from_name(
+                    "edit-delete-symbolic",
+                ))
+                .on_press(Message::RemoveAlarm(index))
+                .into(),
+            ])
+            .spacing(space_s)
+            .align_y(cosmic::iced::Alignment::Center),
+        );
+    }
+
+    content = content
+        .push(cosmic::widget::divider::horizontal::default())
+        .push(cosmic::widget::text::title4(fl!("new-alarm")));
+
+    let draft = &app.alarm_draft;
+
+    let time_row = directional_row(vec![
+        cosmic::widget::text::body(fl!("time")).into(),
+        cosmic::widget::text_input("HH:MM", &draft.time)
+            .on_input(Message::AlarmTimeChanged)
+            .width(cosmic::iced::Length::Fixed(80.0))
+            .into(),
+    ])
+    .spacing(space_s)
+    .align_y(cosmic::iced::Alignment::Center);
+
+    let weekdays_row = (0..7).fold(
+        cosmic::widget::row().spacing(space_s / 2.0),
+        |row, day| {
+            let label = if draft.weekdays[day] {
+                format!("● {}", alarm::weekday_label(day as u32))
+            } else {
+                format!("○ {}", alarm::weekday_label(day as u32))
+            };
+            row.push(
+                cosmic::widget::button::text(label).on_press(Message::ToggleAlarmWeekday(day)),
+            )
+        },
+    );
+
+    content = content
+        .push(time_row)
+        .push(weekdays_row)
+        .push(cosmic::widget::text::caption(fl!("one-time-alarm-hint")));
+
+    // Player to start
+    let discovered_players = app.music_controller.get_discovered_players();
+    let selected_index = match draft.player {
+        None => Some(0),
+        Some(ref player) => discovered_players
+            .iter()
+            .position(|p| &p.identity == player)
+            .map(|index| index + 1),
+    };
+
+    content = content.push(cosmic::widget::radio(
+        cosmic::widget::text::body(fl!("selected-player")),
+        0usize,
+        selected_index,
+        |_| Message::AlarmPlayerChanged(None),
+    ));
+    for (index, player) in discovered_players.iter().enumerate() {
+        let radio = cosmic::widget::radio(
+            cosmic::widget::text::body(display_name(app, &player.identity).to_string()),
+            index + 1,
+            selected_index,
+            {
+                let player_name = player.identity.clone();
+                move |_| Message::AlarmPlayerChanged(Some(player_name.clone()))
+            },
+        );
+        content = content.push(radio);
+    }
+
+    let ramp_row = directional_row(vec![
+        cosmic::widget::text::body(fl!("alarm-ramp", minutes = draft.ramp_minutes)).into(),
+        cosmic::widget::slider(0..=15, draft.ramp_minutes, Message::AlarmRampChanged)
+            .width(cosmic::iced::Length::Fill)
+            .into(),
+    ])
+    .spacing(space_s)
+    .align_y(cosmic::iced::Alignment::Center);
+
+    content = content
+        .push(
+            cosmic::widget::text_input(fl!("alarm-uri"), &draft.uri)
+                .on_input(Message::AlarmUriChanged),
+        )
+        .push(ramp_row)
+        .push(cosmic::widget::text::caption(fl!("alarm-volume-hint")))
+        .push(cosmic::widget::button::text(fl!("add-alarm")).on_press(Message::AddAlarm));
+
+    cosmic::widget::scrollable(content).into()
+}
+
+fn view_system_volume(app: &CosmicAppletMusic, space_s: f32) -> Option<Element<'_, Message>> {
+    if !app.show_system_volume() {
+        return None;
+    }
+    let sink = app.default_sink.as_ref()?;
+
+    let mute_icon = if sink.muted || sink.volume <= 0.0 {
+        "audio-volume-muted-symbolic"
+    } else if sink.volume < 0.5 {
+        "audio-volume-low-symbolic"
+    } else {
+        "audio-volume-high-symbolic"
+    };
+
+    let volume_row = cosmic::widget::row()
+        .spacing(space_s)
+        .push(
+            cosmic::widget::button::icon(cosmic::widget::icon::from_name(mute_icon).size(16))
+                .padding(4)
+                .on_press(Message::ToggleMasterMute),
+        )
+        .push(
+            cosmic::widget::slider(0.0..=1.0, sink.volume, Message::MasterVolumeChanged)
+                .step(0.01)
+                .width(cosmic::iced::Length::Fill),
+        )
+        .push(cosmic::widget::text::caption(format!(
+            "{}%",
+            (sink.volume * 100.0).round()
+        )))
+        .align_y(cosmic::iced::Alignment::Center);
+
+    let selected_sink = app
+        .output_sinks
+        .iter()
+        .position(|s| s.name == sink.name);
+
+    let output_dropdown = cosmic::widget::dropdown(
+        &app.output_sink_labels,
+        selected_sink,
+        Message::SelectDefaultSink,
+    )
+    .width(cosmic::iced::Length::Fill);
+
+    Some(
+        cosmic::widget::column()
+            .spacing(space_s)
+            .push(cosmic::widget::text::caption(fl!("system-volume")))
+            .push(volume_row)
+            .push(output_dropdown)
+            .into(),
+    )
+}
+
+fn view_player_controls(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Element<'_, Message> {
     // Check if "show all players" mode is enabled
     let show_all_players = app
         .config_manager
@@ -81,10 +351,8 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
             cosmic::widget::column()
                 .spacing(space_s)
                 .push(cosmic::widget::icon::from_name("audio-headphones-symbolic").size(48))
-                .push(cosmic::widget::text::body("No player selected"))
-                .push(cosmic::widget::text::caption(
-                    "Go to Settings tab to select a media player",
-                ))
+                .push(cosmic::widget::text::body(fl!("no-player-selected")))
+                .push(cosmic::widget::text::caption(fl!("select-player-hint")))
                 .align_x(cosmic::iced::Alignment::Center),
         )
         .width(cosmic::iced::Length::Fill)
@@ -95,8 +363,8 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
     }
 
     // Album cover
-    let album_cover = if let Some(ref handle) = app.album_art_handle {
-        cosmic::widget::container(
+    let album_cover: Element<'_, Message> = match app.album_art {
+        AlbumArt::Loaded(ref handle) => cosmic::widget::container(
             cosmic::widget::image(handle.clone())
                 .width(cosmic::iced::Length::Fixed(80.0))
                 .height(cosmic::iced::Length::Fixed(80.0))
@@ -105,23 +373,11 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
         .width(cosmic::iced::Length::Fixed(80.0))
         .height(cosmic::iced::Length::Fixed(80.0))
         .class(cosmic::theme::Container::Card)
-    } else if app.player_info.art_url.is_some() {
-        // Loading state
-        cosmic::widget::container(
-            cosmic::widget::column()
-                .push(cosmic::widget::icon::from_name("image-loading-symbolic").size(32))
-                .push(cosmic::widget::text::caption("Loading...").size(10))
-                .spacing(4)
-                .align_x(cosmic::iced::Alignment::Center),
-        )
-        .width(cosmic::iced::Length::Fixed(80.0))
-        .height(cosmic::iced::Length::Fixed(80.0))
-        .align_x(cosmic::iced::alignment::Horizontal::Center)
-        .align_y(cosmic::iced::alignment::Vertical::Center)
-        .class(cosmic::theme::Container::Card)
-    } else {
+        .into(),
+        AlbumArt::Loading => view_art_placeholder("image-loading-symbolic", fl!("loading")),
+        AlbumArt::Failed => view_art_placeholder("image-missing-symbolic", fl!("art-failed")),
         // No art available
-        cosmic::widget::container(
+        AlbumArt::None => cosmic::widget::container(
             cosmic::widget::icon::from_name("audio-headphones-symbolic").size(48),
         )
         .width(cosmic::iced::Length::Fixed(80.0))
@@ -129,19 +385,23 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
         .align_x(cosmic::iced::alignment::Horizontal::Center)
         .align_y(cosmic::iced::alignment::Vertical::Center)
         .class(cosmic::theme::Container::Card)
+        .into(),
     };
 
     let song_info = cosmic::widget::column()
         .spacing(space_s)
-        .push(cosmic::widget::text::title4(&app.player_info.title))
-        .push(cosmic::widget::text::body(&app.player_info.artist));
+        .push(cosmic::widget::text::title4(display_title(&app.player_info)))
+        .push(cosmic::widget::text::body(display_artist(&app.player_info)));
 
-    let info_row = cosmic::widget::row()
+    let info_row = directional_row(vec![album_cover, song_info.into()])
         .spacing(space_m)
-        .push(album_cover)
-        .push(song_info)
         .align_y(cosmic::iced::Alignment::Center);
 
+    let spectrum = app
+        .audio_levels
+        .get(&app.player_info.bus_name)
+        .map(|levels| view_spectrum(&levels.spectrum, space_s));
+
     let status_icon = match app.player_info.status {
         PlaybackStatus::Playing => "media-playback-pause-symbolic", // Show pause when playing
         PlaybackStatus::Paused => "media-playback-start-symbolic",  // Show play when paused
@@ -173,9 +433,15 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
         .spacing(space_s)
         .push(cosmic::widget::icon::from_name("audio-volume-low-symbolic").size(16))
         .push(
-            cosmic::widget::slider(0.0..=1.0, app.player_info.volume, Message::VolumeChanged)
-                .step(0.01)
-                .width(cosmic::iced::Length::Fill),
+            cosmic::widget::slider(
+                0.0..=app.max_volume(),
+                app.player_info.volume,
+                Message::VolumeChanged,
+            )
+            .on_release(Message::VolumeReleased)
+            .step(0.01)
+            .breakpoints(&[1.0])
+            .width(cosmic::iced::Length::Fill),
         )
         .push(cosmic::widget::icon::from_name("audio-volume-high-symbolic").size(16))
         .align_y(cosmic::iced::Alignment::Center);
@@ -183,6 +449,7 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
     cosmic::widget::column()
         .spacing(space_m)
         .push(info_row)
+        .push_maybe(spectrum)
         .push(cosmic::widget::divider::horizontal::default())
         .push(
             cosmic::widget::container(controls)
@@ -194,6 +461,56 @@ fn view_controls_tab(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Ele
         .into()
 }
 
+/// Icon and caption in place of album art that is loading or could not be loaded
+fn view_art_placeholder(icon_name: &'static str, caption: String) -> Element<'static, Message> {
+    cosmic::widget::container(
+        cosmic::widget::column()
+            .push(cosmic::widget::icon::from_name(icon_name).size(32))
+            .push(cosmic::widget::text::caption(caption).size(10))
+            .spacing(4)
+            .align_x(cosmic::iced::Alignment::Center),
+    )
+    .width(cosmic::iced::Length::Fixed(80.0))
+    .height(cosmic::iced::Length::Fixed(80.0))
+    .align_x(cosmic::iced::alignment::Horizontal::Center)
+    .align_y(cosmic::iced::alignment::Vertical::Center)
+    .class(cosmic::theme::Container::Card)
+    .into()
+}
+
+fn view_spectrum(spectrum: &[f32], space_s: f32) -> Element<'static, Message> {
+    let max_height = 40.0;
+
+    let bars = spectrum.iter().fold(
+        cosmic::widget::row().spacing(space_s / 4.0),
+        |row, value| {
+            row.push(
+                cosmic::widget::container(cosmic::widget::horizontal_space())
+                    .width(cosmic::iced::Length::Fill)
+                    .height(cosmic::iced::Length::Fixed((value * max_height).max(1.0)))
+                    .class(cosmic::theme::Container::custom(|theme| {
+                        cosmic::iced::widget::container::Style {
+                            background: Some(cosmic::iced::Background::Color(
+                                theme.cosmic().accent_color().into(),
+                            )),
+                            border: cosmic::iced::Border {
+                                radius: 2.0.into(),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        }
+                    })),
+            )
+        },
+    );
+
+    cosmic::widget::container(bars.align_y(cosmic::iced::Alignment::End))
+        .width(cosmic::iced::Length::Fill)
+        .height(cosmic::iced::Length::Fixed(max_height))
+        .align_y(cosmic::iced::alignment::Vertical::Bottom)
+        .into()
+}
+
 fn view_settings_tab(app: &CosmicAppletMusic, _space_s: f32, space_m: f32) -> Element<'_, Message> {
     // Get discovered players
     let discovered_players = app.music_controller.get_discovered_players();
@@ -201,14 +518,14 @@ fn view_settings_tab(app: &CosmicAppletMusic, _space_s: f32, space_m: f32) -> El
     let mut settings_content = cosmic::widget::column().spacing(space_m);
 
     // Multi-player mode section
-    settings_content = settings_content.push(cosmic::widget::text::title4("Multi-Player Mode"));
+    settings_content = settings_content.push(cosmic::widget::text::title4(fl!("multi-player-mode")));
 
     if let Some(ref config) = app.config_manager {
         let show_all_players = config.get_show_all_players();
         let hide_inactive = config.get_hide_inactive_players();
 
         let show_all_checkbox =
-            cosmic::widget::checkbox("Show all media players", show_all_players)
+            cosmic::widget::checkbox(fl!("show-all-players"), show_all_players)
                 .on_toggle(Message::ToggleShowAllPlayers);
 
         settings_content = settings_content.push(show_all_checkbox);
@@ -216,34 +533,243 @@ fn view_settings_tab(app: &CosmicAppletMusic, _space_s: f32, space_m: f32) -> El
         // Only show "Hide inactive players" option when "Show all players" is enabled
         if show_all_players {
             let hide_inactive_checkbox =
-                cosmic::widget::checkbox("Hide stopped players", hide_inactive)
+                cosmic::widget::checkbox(fl!("hide-stopped-players"), hide_inactive)
                     .on_toggle(Message::ToggleHideInactive);
 
             settings_content = settings_content
-                .push(cosmic::widget::text::caption(
-                    "Hide players that are stopped (keep Playing and Paused)",
-                ))
+                .push(cosmic::widget::text::caption(fl!("hide-stopped-players-hint")))
                 .push(hide_inactive_checkbox);
         }
     }
 
     settings_content = settings_content.push(cosmic::widget::divider::horizontal::default());
 
+    // Volume section
+    settings_content = settings_content.push(cosmic::widget::text::title4(fl!("volume")));
+
+    if let Some(ref config) = app.config_manager {
+        let system_volume_checkbox = cosmic::widget::checkbox(
+            fl!("show-system-volume"),
+            config.get_show_system_volume(),
+        )
+        .on_toggle(Message::ToggleShowSystemVolume);
+
+        settings_content = settings_content.push(system_volume_checkbox);
+
+        let max_volume_percent = config.get_max_volume_percent();
+
+        let max_volume_row = MAX_VOLUME_OPTIONS.iter().fold(
+            cosmic::widget::row().spacing(space_m),
+            |row, percent| {
+                row.push(cosmic::widget::radio(
+                    cosmic::widget::text::body(format!("{}%", percent)),
+                    *percent,
+                    Some(max_volume_percent),
+                    Message::SetMaxVolume,
+                ))
+            },
+        );
+
+        settings_content = settings_content
+            .push(cosmic::widget::text::caption(fl!("max-volume-hint")))
+            .push(max_volume_row);
+    }
+
+    // Per-player opt-out of volume restoring
+    if let Some(ref config) = app.config_manager {
+        if !discovered_players.is_empty() {
+            settings_content = settings_content.push(cosmic::widget::text::caption(fl!("restore-volume-hint")));
+        }
+
+        for player in discovered_players.iter() {
+            let remember_checkbox = cosmic::widget::checkbox(
+                display_name(app, &player.identity).to_string(),
+                config.get_remember_volume(&player.identity),
+            )
+            .on_toggle({
+                let player_name = player.identity.clone();
+                move |enabled| Message::ToggleRememberVolume(player_name.clone(), enabled)
+            });
+
+            settings_content = settings_content.push(remember_checkbox);
+        }
+    }
+
+    settings_content = settings_content.push(cosmic::widget::divider::horizontal::default());
+
+    // Fade section
+    settings_content = settings_content.push(cosmic::widget::text::title4(fl!("fading")));
+
+    if let Some(ref config) = app.config_manager {
+        let fade_enabled = config.get_fade_on_play_pause();
+
+        let fade_checkbox =
+            cosmic::widget::checkbox(fl!("fade-on-play-pause"), fade_enabled)
+                .on_toggle(Message::ToggleFade);
+
+        settings_content = settings_content.push(fade_checkbox);
+
+        if fade_enabled {
+            let duration_ms = config.get_fade_duration_ms();
+
+            let duration_row = cosmic::widget::row()
+                .spacing(space_m)
+                .push(
+                    cosmic::widget::slider(250..=5000, duration_ms, Message::FadeDurationChanged)
+                        .step(250u32)
+                        .width(cosmic::iced::Length::Fill),
+                )
+                .push(cosmic::widget::text::caption(fl!(
+                    "fade-duration-value",
+                    seconds = format!("{:.2}", f64::from(duration_ms) / 1000.0)
+                )))
+                .align_y(cosmic::iced::Alignment::Center);
+
+            settings_content = settings_content
+                .push(cosmic::widget::text::caption(fl!("fade-duration")))
+                .push(duration_row);
+        }
+    }
+
+    settings_content = settings_content.push(cosmic::widget::divider::horizontal::default());
+
+    // Ducking section
+    settings_content = settings_content.push(cosmic::widget::text::title4(fl!("ducking")));
+
+    if let Some(ref config) = app.config_manager {
+        let ducking_enabled = config.get_ducking_enabled();
+
+        let ducking_checkbox =
+            cosmic::widget::checkbox(fl!("ducking-enabled"), ducking_enabled)
+                .on_toggle(Message::ToggleDucking);
+
+        settings_content = settings_content.push(ducking_checkbox);
+
+        if ducking_enabled {
+            let amount = config.get_ducking_amount_percent();
+
+            let amount_row = cosmic::widget::row()
+                .spacing(space_m)
+                .push(
+                    cosmic::widget::slider(
+                        DUCKING_AMOUNT_RANGE,
+                        amount,
+                        Message::DuckingAmountChanged,
+                    )
+                    .step(5u32)
+                    .width(cosmic::iced::Length::Fill),
+                )
+                .push(cosmic::widget::text::caption(format!("-{}%", amount)))
+                .align_y(cosmic::iced::Alignment::Center);
+
+            let recording_checkbox = cosmic::widget::checkbox(
+                fl!("ducking-on-recording"),
+                config.get_ducking_on_recording(),
+            )
+            .on_toggle(Message::ToggleDuckingOnRecording);
+
+            settings_content = settings_content
+                .push(cosmic::widget::text::caption(fl!("ducking-amount")))
+                .push(amount_row)
+                .push(recording_checkbox);
+        }
+    }
+
+    settings_content = settings_content.push(cosmic::widget::divider::horizontal::default());
+
+    // Visualizer section
+    settings_content = settings_content.push(cosmic::widget::text::title4(fl!("visualizer")));
+
+    if let Some(ref config) = app.config_manager {
+        let visualizer_checkbox =
+            cosmic::widget::checkbox(fl!("show-visualizer"), config.get_show_visualizer())
+                .on_toggle(Message::ToggleVisualizer);
+
+        settings_content = settings_content
+            .push(cosmic::widget::text::caption(fl!("visualizer-hint")))
+            .push(visualizer_checkbox);
+    }
+
+    settings_content = settings_content.push(cosmic::widget::divider::horizontal::default());
+
+    // Refresh section
+    settings_content = settings_content.push(cosmic::widget::text::title4(fl!("refresh")));
+
+    if let Some(ref config) = app.config_manager {
+        let refresh_ms = config.get_refresh_interval_ms();
+        let idle_refresh_ms = config.get_idle_refresh_interval_ms();
+
+        let refresh_row = directional_row(vec![
+            cosmic::widget::text::body(fl!("refresh-active", ms = refresh_ms)).into(),
+            cosmic::widget::slider(250..=2000, refresh_ms, Message::RefreshIntervalChanged)
+                .step(250u32)
+                .width(cosmic::iced::Length::Fill)
+                .into(),
+        ])
+        .spacing(space_m)
+        .align_y(cosmic::iced::Alignment::Center);
+
+        let idle_refresh_row = directional_row(vec![
+            cosmic::widget::text::body(fl!("refresh-idle", seconds = idle_refresh_ms / 1000))
+                .into(),
+            cosmic::widget::slider(
+                1000..=10000,
+                idle_refresh_ms,
+                Message::IdleRefreshIntervalChanged,
+            )
+            .step(1000u32)
+            .width(cosmic::iced::Length::Fill)
+            .into(),
+        ])
+        .spacing(space_m)
+        .align_y(cosmic::iced::Alignment::Center);
+
+        settings_content = settings_content
+            .push(cosmic::widget::text::caption(fl!("refresh-hint")))
+            .push(refresh_row)
+            .push(idle_refresh_row);
+    }
+
+    settings_content = settings_content.push(cosmic::widget::divider::horizontal::default());
+
+    // Album art section
+    settings_content = settings_content.push(cosmic::widget::text::title4(fl!("album-art")));
+
+    if let Some(ref config) = app.config_manager {
+        let size_mb = config.get_art_cache_size_mb();
+
+        let cache_row = directional_row(vec![
+            cosmic::widget::text::body(fl!("art-cache-size", megabytes = size_mb)).into(),
+            cosmic::widget::slider(10..=500, size_mb, Message::ArtCacheSizeChanged)
+                .step(10u32)
+                .width(cosmic::iced::Length::Fill)
+                .into(),
+        ])
+        .spacing(space_m)
+        .align_y(cosmic::iced::Alignment::Center);
+
+        settings_content = settings_content
+            .push(cosmic::widget::text::caption(fl!("art-cache-hint")))
+            .push(cache_row);
+    }
+
+    settings_content = settings_content.push(cosmic::widget::divider::horizontal::default());
+
     // Auto-detect section
-    settings_content = settings_content.push(cosmic::widget::text::title4("Player Discovery"));
+    settings_content = settings_content.push(cosmic::widget::text::title4(fl!("player-discovery")));
 
     if let Some(ref config) = app.config_manager {
         let auto_detect_enabled = config.get_auto_detect_new_players();
 
         let auto_detect_checkbox =
-            cosmic::widget::checkbox("Auto-detect new players", auto_detect_enabled)
+            cosmic::widget::checkbox(fl!("auto-detect-players"), auto_detect_enabled)
                 .on_toggle(Message::ToggleAutoDetect);
 
         settings_content = settings_content.push(auto_detect_checkbox);
     }
 
     // Discover Players button
-    let discover_button = cosmic::widget::button::text("Discover Players")
+    let discover_button = cosmic::widget::button::text(fl!("discover-players"))
         .on_press(Message::DiscoverPlayers)
         .width(cosmic::iced::Length::Fill);
 
@@ -259,13 +785,11 @@ fn view_settings_tab(app: &CosmicAppletMusic, _space_s: f32, space_m: f32) -> El
         .unwrap_or(false);
 
     if !show_all_players {
-        settings_content = settings_content.push(cosmic::widget::text::title4("Player Selection"));
+        settings_content = settings_content.push(cosmic::widget::text::title4(fl!("player-selection")));
 
-        settings_content = settings_content.push(cosmic::widget::text::caption(
-            "Choose which media player to control:",
-        ));
+        settings_content = settings_content.push(cosmic::widget::text::caption(fl!("choose-player")));
     } else {
-        settings_content = settings_content.push(cosmic::widget::text::title4("Discovered Players"));
+        settings_content = settings_content.push(cosmic::widget::text::title4(fl!("discovered-players")));
     }
 
     // Only show player selection in single-player mode
@@ -293,19 +817,31 @@ fn view_settings_tab(app: &CosmicAppletMusic, _space_s: f32, space_m: f32) -> El
 
         // "None" option to disable all players
         let none_radio =
-            cosmic::widget::radio("None (disable all players)", 0usize, selected_index, |_| {
-                Message::SelectPlayer(None)
-            });
+            cosmic::widget::radio(
+                cosmic::widget::text::body(fl!("no-player")),
+                0usize,
+                selected_index,
+                |_| Message::SelectPlayer(None),
+            );
         settings_content = settings_content.push(none_radio);
 
-        // Add radio buttons for each discovered player
+        // Add radio buttons for each discovered player, leaving out hidden ones unless selected
         for (index, player) in discovered_players.iter().enumerate() {
-            let status_text = if player.is_active {
-                " (♪ currently playing)"
+            if selected_index != Some(index + 1)
+                && app
+                    .config_manager
+                    .as_ref()
+                    .is_some_and(|config| config.is_player_hidden(&player.identity))
+            {
+                continue;
+            }
+
+            let name = display_name(app, &player.identity);
+            let radio_text = if player.is_active {
+                fl!("currently-playing", player = name)
             } else {
-                ""
+                name.to_string()
             };
-            let radio_text = format!("{}{}", player.identity, status_text);
 
             let radio = cosmic::widget::radio(
                 cosmic::widget::text::body(radio_text),
@@ -320,59 +856,332 @@ fn view_settings_tab(app: &CosmicAppletMusic, _space_s: f32, space_m: f32) -> El
         }
 
         if discovered_players.is_empty() {
-            settings_content = settings_content.push(cosmic::widget::text::caption(
-                "No players discovered yet. Click 'Discover Players' to search.",
-            ));
+            settings_content = settings_content.push(cosmic::widget::text::caption(fl!("no-players-discovered")));
         }
     } else {
         // In multi-player mode, just list the discovered players
         if discovered_players.is_empty() {
-            settings_content = settings_content.push(cosmic::widget::text::caption(
-                "No players discovered yet. Click 'Discover Players' to search.",
-            ));
+            settings_content = settings_content.push(cosmic::widget::text::caption(fl!("no-players-discovered")));
         } else {
             for player in discovered_players.iter() {
                 let status_text = if player.is_active { " ♪" } else { "" };
-                let player_text = format!("{}{}", player.identity, status_text);
+                let player_text =
+                    format!("{}{}", display_name(app, &player.identity), status_text);
 
                 settings_content = settings_content.push(cosmic::widget::text::body(player_text));
             }
         }
     }
 
+    settings_content = settings_content
+        .push(cosmic::widget::divider::horizontal::default())
+        .push(view_panel_actions(app, space_m))
+        .push(cosmic::widget::divider::horizontal::default())
+        .push(view_panel_text_settings(app, space_m))
+        .push(cosmic::widget::divider::horizontal::default())
+        .push(view_player_profiles(app, space_m))
+        .push(cosmic::widget::divider::horizontal::default())
+        .push(view_settings_file(app, space_m));
+
     cosmic::widget::scrollable(settings_content).into()
 }
 
-fn view_all_players(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Element<'_, Message> {
-    let hide_inactive = app
-        .config_manager
-        .as_ref()
-        .map(|config| config.get_hide_inactive_players())
-        .unwrap_or(false);
+fn view_panel_actions(app: &CosmicAppletMusic, space_m: f32) -> Element<'_, Message> {
+    let mut content = cosmic::widget::column()
+        .spacing(space_m)
+        .push(cosmic::widget::text::title4(fl!("panel-button")));
+
+    let Some(ref config) = app.config_manager else {
+        return content.into();
+    };
+    let actions = config.get_panel_actions();
+
+    for gesture in PANEL_GESTURES {
+        let selected = PANEL_ACTIONS
+            .iter()
+            .position(|action| *action == actions.get(gesture));
+
+        content = content.push(
+            directional_row(vec![
+                cosmic::widget::text::body(panel::gesture_label(gesture))
+                    .width(cosmic::iced::Length::Fill)
+                    .into(),
+                cosmic::widget::dropdown(&*PANEL_ACTION_LABELS, selected, move |index| {
+                    Message::SetPanelAction(gesture, PANEL_ACTIONS[index])
+                })
+                .width(cosmic::iced::Length::Fixed(160.0))
+                .into(),
+            ])
+            .spacing(space_m)
+            .align_y(cosmic::iced::Alignment::Center),
+        );
+    }
+
+    let threshold_row = directional_row(vec![
+        cosmic::widget::text::body(fl!(
+            "scroll-distance",
+            lines = format!("{:.1}", actions.scroll_threshold)
+        ))
+        .into(),
+        cosmic::widget::slider(
+            0.5..=5.0,
+            actions.scroll_threshold,
+            Message::ScrollThresholdChanged,
+        )
+        .step(0.5)
+        .width(cosmic::iced::Length::Fill)
+        .into(),
+    ])
+    .spacing(space_m)
+    .align_y(cosmic::iced::Alignment::Center);
+
+    content
+        .push(cosmic::widget::text::caption(fl!("panel-button-hint")))
+        .push(threshold_row)
+        .into()
+}
+
+fn view_panel_text_settings(app: &CosmicAppletMusic, space_m: f32) -> Element<'_, Message> {
+    let mut content = cosmic::widget::column()
+        .spacing(space_m)
+        .push(cosmic::widget::text::title4(fl!("panel-text")));
+
+    let Some(ref config) = app.config_manager else {
+        return content.into();
+    };
+
+    let show_checkbox =
+        cosmic::widget::checkbox(fl!("show-panel-text"), config.get_show_panel_text())
+            .on_toggle(Message::ToggleShowPanelText);
+    content = content.push(show_checkbox);
+
+    if !config.get_show_panel_text() {
+        return content.into();
+    }
+
+    let max_chars = config.get_panel_text_max_chars();
+    let width_row = directional_row(vec![
+        cosmic::widget::text::body(fl!("panel-text-width", chars = max_chars)).into(),
+        cosmic::widget::slider(10..=80, max_chars, Message::PanelTextMaxCharsChanged)
+            .step(5u32)
+            .width(cosmic::iced::Length::Fill)
+            .into(),
+    ])
+    .spacing(space_m)
+    .align_y(cosmic::iced::Alignment::Center);
+
+    let marquee_checkbox =
+        cosmic::widget::checkbox(fl!("panel-text-marquee"), config.get_panel_text_marquee())
+            .on_toggle(Message::TogglePanelTextMarquee);
+
+    let vertical = Some(config.get_vertical_panel_text());
+    let vertical_row = cosmic::widget::row()
+        .spacing(space_m)
+        .push(cosmic::widget::radio(
+            cosmic::widget::text::body(fl!("vertical-hide")),
+            VerticalPanelText::Hide,
+            vertical,
+            Message::SetVerticalPanelText,
+        ))
+        .push(cosmic::widget::radio(
+            cosmic::widget::text::body(fl!("vertical-stack")),
+            VerticalPanelText::Stack,
+            vertical,
+            Message::SetVerticalPanelText,
+        ));
+
+    content
+        .push(width_row)
+        .push(marquee_checkbox)
+        .push(cosmic::widget::text::caption(fl!("vertical-panels")))
+        .push(vertical_row)
+        .into()
+}
+
+fn view_player_profiles(app: &CosmicAppletMusic, space_m: f32) -> Element<'_, Message> {
+    let mut content = cosmic::widget::column()
+        .spacing(space_m)
+        .push(cosmic::widget::text::title4(fl!("player-profiles")));
+
+    let Some(ref config) = app.config_manager else {
+        return content.into();
+    };
 
-    // Filter players based on hide_inactive setting
-    // Only hide Stopped players, keep Playing and Paused visible
-    let players_to_show: Vec<_> = app
-        .all_players_info
+    // Pick which player to customize
+    let discovered_players = app.music_controller.get_discovered_players();
+    let mut identities: Vec<&str> = discovered_players
         .iter()
-        .filter(|player| {
-            if hide_inactive {
-                player.status != PlaybackStatus::Stopped
-            } else {
-                true
-            }
-        })
+        .map(|player| player.identity.as_str())
         .collect();
+    if let Some(ref editing) = app.editing_profile {
+        if !identities.contains(&editing.as_str()) {
+            identities.push(editing);
+        }
+    }
+
+    for identity in identities {
+        let editing = app.editing_profile.as_deref() == Some(identity);
+        let label = if editing {
+            format!("● {}", config.get_display_name(identity))
+        } else {
+            format!("○ {}", config.get_display_name(identity))
+        };
+        content = content.push(
+            cosmic::widget::button::text(label)
+                .on_press(Message::EditPlayerProfile(if editing {
+                    None
+                } else {
+                    Some(identity.to_string())
+                }))
+                .width(cosmic::iced::Length::Fill),
+        );
+    }
+
+    let Some(ref identity) = app.editing_profile else {
+        return content
+            .push(cosmic::widget::text::caption(fl!("player-profiles-hint")))
+            .into();
+    };
+    let profile = config.get_player_profile(identity).cloned().unwrap_or_default();
+
+    // Every edit saves the whole profile
+    let changed = {
+        let identity = identity.clone();
+        let profile = profile.clone();
+        move |update: &dyn Fn(&mut PlayerProfile)| {
+            let mut profile = profile.clone();
+            update(&mut profile);
+            Message::PlayerProfileChanged(identity.clone(), profile)
+        }
+    };
+    let non_empty = |value: String| Some(value).filter(|value| !value.trim().is_empty());
+
+    let display_name_input = cosmic::widget::text_input(
+        identity.as_str(),
+        profile.display_name.as_deref().unwrap_or(""),
+    )
+    .on_input({
+        let changed = changed.clone();
+        move |name| changed(&|profile| profile.display_name = non_empty(name.clone()))
+    });
+
+    let icon_input = cosmic::widget::text_input(
+        fl!("icon-name-placeholder"),
+        profile.icon_name.as_deref().unwrap_or(""),
+    )
+    .on_input({
+        let changed = changed.clone();
+        move |icon_name| changed(&|profile| profile.icon_name = non_empty(icon_name.clone()))
+    });
+
+    let hidden_checkbox = cosmic::widget::checkbox(fl!("hide-player"), profile.hidden).on_toggle({
+        let changed = changed.clone();
+        move |hidden| changed(&|profile| profile.hidden = hidden)
+    });
+
+    let default_volume_checkbox = cosmic::widget::checkbox(
+        fl!("default-volume"),
+        profile.default_volume.is_some(),
+    )
+    .on_toggle({
+        let changed = changed.clone();
+        move |enabled| changed(&|profile| profile.default_volume = enabled.then_some(0.5))
+    });
+
+    let default_volume_slider = profile.default_volume.map(|volume| {
+        let changed = changed.clone();
+        cosmic::widget::slider(0.0..=app.max_volume(), volume, move |volume| {
+            changed(&|profile| profile.default_volume = Some(volume))
+        })
+        .step(0.01)
+    });
+
+    // Output: system default (0) or one of the known sinks
+    let selected_output = match profile.preferred_output {
+        None => Some(0),
+        Some(ref name) => app
+            .output_sinks
+            .iter()
+            .position(|sink| &sink.name == name)
+            .map(|index| index + 1),
+    };
+    let mut outputs = cosmic::widget::column().spacing(4.0).push(cosmic::widget::radio(
+        cosmic::widget::text::body(fl!("system-default-output")),
+        0usize,
+        selected_output,
+        {
+            let changed = changed.clone();
+            move |_| changed(&|profile| profile.preferred_output = None)
+        },
+    ));
+    for (index, (sink, label)) in app.output_sinks.iter().zip(&app.output_sink_labels).enumerate() {
+        outputs = outputs.push(cosmic::widget::radio(
+            label.as_str(),
+            index + 1,
+            selected_output,
+            {
+                let changed = changed.clone();
+                let name = sink.name.clone();
+                move |_| changed(&|profile| profile.preferred_output = Some(name.clone()))
+            },
+        ));
+    }
+
+    content
+        .push(cosmic::widget::text::caption(fl!("display-name")))
+        .push(display_name_input)
+        .push(cosmic::widget::text::caption(fl!("icon")))
+        .push(icon_input)
+        .push(hidden_checkbox)
+        .push(default_volume_checkbox)
+        .push_maybe(default_volume_slider)
+        .push(cosmic::widget::text::caption(fl!("output")))
+        .push(outputs)
+        .into()
+}
+
+fn view_settings_file(app: &CosmicAppletMusic, space_m: f32) -> Element<'_, Message> {
+    let buttons = cosmic::widget::row()
+        .spacing(space_m)
+        .push(
+            cosmic::widget::button::text(fl!("export-settings"))
+                .on_press(Message::ExportSettings)
+                .width(cosmic::iced::Length::Fill),
+        )
+        .push(
+            cosmic::widget::button::text(fl!("import-settings"))
+                .on_press(Message::ImportSettings)
+                .width(cosmic::iced::Length::Fill),
+        );
+
+    cosmic::widget::column()
+        .spacing(space_m)
+        .push(cosmic::widget::text::title4(fl!("settings-file")))
+        .push(cosmic::widget::text::caption(fl!("settings-file-hint")))
+        .push(
+            cosmic::widget::text_input(fl!("path"), &app.settings_file_path)
+                .on_input(Message::SettingsFilePathChanged),
+        )
+        .push(buttons)
+        .push_maybe(
+            app.settings_file_status
+                .as_ref()
+                .map(|status| cosmic::widget::text::caption(status.clone())),
+        )
+        .into()
+}
+
+fn view_all_players(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Element<'_, Message> {
+    // Stopped players may be hidden, Playing and Paused ones stay visible
+    let players_to_show = app.visible_player_cards();
 
     if players_to_show.is_empty() {
         return cosmic::widget::container(
             cosmic::widget::column()
                 .spacing(space_s)
                 .push(cosmic::widget::icon::from_name("audio-headphones-symbolic").size(48))
-                .push(cosmic::widget::text::body("No media playing"))
-                .push(cosmic::widget::text::caption(
-                    "Start playing media in any MPRIS-compatible player",
-                ))
+                .push(cosmic::widget::text::body(fl!("no-media-playing")))
+                .push(cosmic::widget::text::caption(fl!("start-media-hint")))
                 .align_x(cosmic::iced::Alignment::Center),
         )
         .width(cosmic::iced::Length::Fill)
@@ -394,24 +1203,26 @@ fn view_all_players(app: &CosmicAppletMusic, space_s: f32, space_m: f32) -> Elem
 }
 
 fn view_player_card<'a>(
-    _app: &'a CosmicAppletMusic,
+    app: &'a CosmicAppletMusic,
     player: &'a crate::music::PlayerInfo,
     space_s: f32,
     _space_m: f32,
 ) -> Element<'a, Message> {
-    // Compact view: no album cover, just text and controls
+    // Compact view: a small album cover next to the text and controls
 
     // Truncate long titles/artists
     let max_length = 40;
-    let title = if player.title.len() > max_length {
-        format!("{}...", &player.title[0..max_length])
+    let title = display_title(player);
+    let title = if title.len() > max_length {
+        format!("{}...", &title[0..max_length])
     } else {
-        player.title.clone()
+        title
     };
-    let artist = if player.artist.len() > max_length {
-        format!("{}...", &player.artist[0..max_length])
+    let artist = display_artist(player);
+    let artist = if artist.len() > max_length {
+        format!("{}...", &artist[0..max_length])
     } else {
-        player.artist.clone()
+        artist
     };
 
     // Status indicator emoji
@@ -421,17 +1232,57 @@ fn view_player_card<'a>(
         PlaybackStatus::Stopped => "⏹",
     };
 
-    // Compact title row with status and identity
-    let title_row = cosmic::widget::row()
+    let profile = app
+        .config_manager
+        .as_ref()
+        .and_then(|config| config.get_player_profile(&player.identity));
+    let display_name = profile
+        .and_then(|profile| profile.display_name.as_deref())
+        .unwrap_or(&player.identity);
+
+    // Compact title row with status and player name
+    let mut title_children: Vec<Element<'_, Message>> = Vec::new();
+    match app.player_album_arts.get(&player.bus_name) {
+        Some((_, AlbumArt::Loaded(handle))) => title_children.push(
+            cosmic::widget::container(
+                cosmic::widget::image(handle.clone())
+                    .width(cosmic::iced::Length::Fixed(40.0))
+                    .height(cosmic::iced::Length::Fixed(40.0))
+                    .content_fit(cosmic::iced::ContentFit::Cover),
+            )
+            .width(cosmic::iced::Length::Fixed(40.0))
+            .height(cosmic::iced::Length::Fixed(40.0))
+            .class(cosmic::theme::Container::Card)
+            .into(),
+        ),
+        // Art that could not be read or decoded
+        Some((_, AlbumArt::Failed)) => title_children.push(
+            cosmic::widget::container(
+                cosmic::widget::icon::from_name("image-missing-symbolic").size(24),
+            )
+            .width(cosmic::iced::Length::Fixed(40.0))
+            .height(cosmic::iced::Length::Fixed(40.0))
+            .align_x(cosmic::iced::alignment::Horizontal::Center)
+            .align_y(cosmic::iced::alignment::Vertical::Center)
+            .class(cosmic::theme::Container::Card)
+            .into(),
+        ),
+        _ => {}
+    }
+    if let Some(icon_name) = profile.and_then(|profile| profile.icon_name.as_deref()) {
+        title_children.push(cosmic::widget::icon::from_name(icon_name).size(24).into());
+    }
+    title_children.push(cosmic::widget::text::body(status_indicator).into());
+    title_children.push(
+        cosmic::widget::column()
+            .spacing(2.0)
+            .push(cosmic::widget::text::body(title).size(12))
+            .push(cosmic::widget::text::caption(artist).size(10))
+            .push(cosmic::widget::text::caption(display_name).size(9))
+            .into(),
+    );
+    let title_row = directional_row(title_children)
         .spacing(space_s)
-        .push(cosmic::widget::text::body(status_indicator))
-        .push(
-            cosmic::widget::column()
-                .spacing(2.0)
-                .push(cosmic::widget::text::body(title).size(12))
-                .push(cosmic::widget::text::caption(artist).size(10))
-                .push(cosmic::widget::text::caption(&player.identity).size(9))
-        )
         .align_y(cosmic::iced::Alignment::Center);
 
     let status_icon = match player.status {
@@ -441,6 +1292,7 @@ fn view_player_card<'a>(
     };
 
     let bus_name = player.bus_name.clone();
+    let expanded = app.expanded_players.contains(&bus_name);
 
     // Compact controls - smaller icons
     let controls = cosmic::widget::row()
@@ -475,32 +1327,58 @@ fn view_player_card<'a>(
                 Message::NextPlayer(bus_name)
             }),
         )
+        .push(
+            cosmic::widget::button::icon(
+                cosmic::widget::icon::from_name(if expanded {
+                    "pan-up-symbolic"
+                } else {
+                    "pan-down-symbolic"
+                })
+                .size(16)
+            )
+            .padding(4)
+            .on_press({
+                let bus_name = bus_name.clone();
+                Message::ToggleAdvancedPlayer(bus_name)
+            }),
+        )
         .align_y(cosmic::iced::Alignment::Center);
 
     // Controls row - title on left, buttons on right
-    let controls_row = cosmic::widget::row()
-        .spacing(space_s)
-        .push(title_row)
-        .push(cosmic::widget::horizontal_space())
-        .push(controls)
-        .align_y(cosmic::iced::Alignment::Center)
-        .width(cosmic::iced::Length::Fill);
+    let controls_row = directional_row(vec![
+        title_row.into(),
+        cosmic::widget::horizontal_space().into(),
+        controls.into(),
+    ])
+    .spacing(space_s)
+    .align_y(cosmic::iced::Alignment::Center)
+    .width(cosmic::iced::Length::Fill);
 
     // Volume control (only show if supported)
     let mut card_content = cosmic::widget::column()
         .spacing(space_s / 2.0)
         .push(controls_row);
 
+    // Tiny level meter while the visualizer captures this player
+    if let Some(levels) = app.audio_levels.get(&player.bus_name) {
+        card_content = card_content.push(
+            cosmic::widget::progress_bar(0.0..=1.0, levels.level)
+                .height(cosmic::iced::Length::Fixed(3.0)),
+        );
+    }
+
     if player.can_control_volume {
         let volume_row = cosmic::widget::row()
             .spacing(space_s / 2.0)
             .push(cosmic::widget::icon::from_name("audio-volume-low-symbolic").size(12))
             .push(
-                cosmic::widget::slider(0.0..=1.0, player.volume, {
+                cosmic::widget::slider(0.0..=app.max_volume(), player.volume, {
                     let bus_name = bus_name.clone();
                     move |v| Message::VolumeChangedPlayer(bus_name.clone(), v)
                 })
+                .on_release(Message::VolumeReleasedPlayer(bus_name.clone()))
                 .step(0.01)
+                .breakpoints(&[1.0])
                 .width(cosmic::iced::Length::Fill),
             )
             .push(cosmic::widget::icon::from_name("audio-volume-high-symbolic").size(12))
@@ -509,9 +1387,64 @@ fn view_player_card<'a>(
         card_content = card_content.push(volume_row);
     }
 
+    // Advanced section (balance is only available for players with a matched audio stream)
+    if expanded {
+        if let Some(balance) = player.balance {
+            let balance_row = cosmic::widget::row()
+                .spacing(space_s / 2.0)
+                .push(cosmic::widget::text::caption(fl!("balance-left")).size(10))
+                .push(
+                    cosmic::widget::slider(-1.0..=1.0, balance, {
+                        let bus_name = bus_name.clone();
+                        move |b| Message::BalanceChangedPlayer(bus_name.clone(), b)
+                    })
+                    .step(0.05)
+                    .width(cosmic::iced::Length::Fill),
+                )
+                .push(cosmic::widget::text::caption(fl!("balance-right")).size(10))
+                .align_y(cosmic::iced::Alignment::Center);
+
+            card_content = card_content
+                .push(cosmic::widget::text::caption(fl!("balance")).size(10))
+                .push(balance_row);
+        } else {
+            card_content = card_content.push(
+                cosmic::widget::text::caption(fl!("no-audio-stream")).size(10),
+            );
+        }
+    }
+
+    // The card keyboard shortcuts act on gets a focus ring
+    let card_class = if app.focused_player.as_ref() == Some(&player.bus_name) {
+        cosmic::theme::Container::custom(|theme| {
+            let cosmic = theme.cosmic();
+            cosmic::iced::widget::container::Style {
+                background: Some(cosmic::iced::Background::Color(
+                    cosmic.primary.component.base.into(),
+                )),
+                border: cosmic::iced::Border {
+                    color: cosmic.accent_color().into(),
+                    width: 2.0,
+                    radius: cosmic.corner_radii.radius_s.into(),
+                },
+                ..Default::default()
+            }
+        })
+    } else {
+        cosmic::theme::Container::Card
+    };
+
     cosmic::widget::container(card_content)
         .padding([space_s, space_s * 1.5])
-        .class(cosmic::theme::Container::Card)
+        .class(card_class)
         .width(cosmic::iced::Length::Fill)
         .into()
 }
+
+/// The player's display name from its profile, or its identity
+fn display_name<'a>(app: &'a CosmicAppletMusic, identity: &'a str) -> &'a str {
+    app.config_manager
+        .as_ref()
+        .map(|config| config.get_display_name(identity))
+        .unwrap_or(identity)
+}
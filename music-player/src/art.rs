@@ -0,0 +1,340 @@
+//! Album art loading from the `mpris:artUrl` players report.
+//!
+//! Players use `file://` URLs for covers they extracted or cached on disk (Spotify,
+//! Rhythmbox, Strawberry, browsers), `data:` URIs for embedded covers and HTTP(S) for
+//! streamed ones. Covers are decoded and scaled down off the UI thread, so only small
+//! thumbnails are kept in memory.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use cosmic::iced::widget::image::Handle;
+use futures::StreamExt;
+use image::{ImageReader, Limits};
+use std::io::Cursor;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+pub mod cache;
+
+pub use cache::MemoryCache;
+
+/// Downloads that take longer than this are given up on
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Covers bigger than this are rejected before decoding
+const MAX_ART_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Covers wider or taller than this are rejected while decoding
+const MAX_ART_DIMENSION: u32 = 8192;
+
+/// Covers are scaled down to fit this many pixels, twice the largest size the popup shows
+/// them at so they stay sharp on scaled displays
+const THUMBNAIL_SIZE: u32 = 160;
+
+static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+/// Album art of a player, as shown in the popup
+#[derive(Debug, Clone, Default)]
+pub enum AlbumArt {
+    /// The player reports no art
+    #[default]
+    None,
+    Loading,
+    Loaded(Handle),
+    /// The art could not be read or decoded, it is not retried until the URL changes
+    Failed,
+}
+
+/// Loads the art behind `url` into a thumbnail, keeping downloads in the disk cache of up
+/// to `cache_limit_bytes`
+pub async fn load(url: &str, cache_limit_bytes: u64) -> AlbumArt {
+    match load_cached(url, cache_limit_bytes).await {
+        Ok(handle) => AlbumArt::Loaded(handle),
+        Err(e) => {
+            eprintln!("Failed to load album art from {}: {:#}", preview(url), e);
+            AlbumArt::Failed
+        }
+    }
+}
+
+/// Serves downloads from the disk cache, local art is read directly
+async fn load_cached(url: &str, cache_limit_bytes: u64) -> Result<Handle> {
+    if !is_remote(url) {
+        let (handle, _) = decode_in_background(load_bytes(url).await?).await?;
+        return Ok(handle);
+    }
+    // A cached cover that no longer decodes is downloaded again
+    if let Some(bytes) = cache::read(url).await {
+        if let Ok((handle, _)) = decode_in_background(bytes).await {
+            return Ok(handle);
+        }
+    }
+
+    // Only covers that decode are cached
+    let (handle, bytes) = decode_in_background(load_bytes(url).await?).await?;
+    if let Err(e) = cache::write(url, &bytes, cache_limit_bytes).await {
+        eprintln!("Failed to cache album art: {}", e);
+    }
+    Ok(handle)
+}
+
+/// Runs [`decode`] on the blocking pool, handing the bytes back for caching
+async fn decode_in_background(bytes: Vec<u8>) -> Result<(Handle, Vec<u8>)> {
+    tokio::task::spawn_blocking(move || decode(&bytes).map(|handle| (handle, bytes))).await?
+}
+
+/// Decodes an encoded cover into a thumbnail of at most [`THUMBNAIL_SIZE`] pixels
+fn decode(bytes: &[u8]) -> Result<Handle> {
+    check_size(bytes.len() as u64)?;
+
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    let Some(format) = reader.format() else {
+        bail!("not a known image format");
+    };
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_ART_DIMENSION);
+    limits.max_image_height = Some(MAX_ART_DIMENSION);
+    reader.limits(limits);
+
+    let image = reader
+        .decode()
+        .with_context(|| format!("decoding {:?}", format))?;
+    let image = if image.width() > THUMBNAIL_SIZE || image.height() > THUMBNAIL_SIZE {
+        image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+    } else {
+        image
+    };
+
+    let rgba = image.into_rgba8();
+    Ok(Handle::from_rgba(rgba.width(), rgba.height(), rgba.into_raw()))
+}
+
+fn check_size(len: u64) -> Result<()> {
+    if len > MAX_ART_BYTES {
+        bail!("{} bytes is over the {} byte limit", len, MAX_ART_BYTES);
+    }
+    Ok(())
+}
+
+fn is_remote(url: &str) -> bool {
+    let url = url.trim_start();
+    ["http:", "https:"].iter().any(|scheme| {
+        url.get(..scheme.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// Reads the raw image bytes behind `url`
+pub async fn load_bytes(url: &str) -> Result<Vec<u8>> {
+    let url = url.trim();
+    let scheme = url
+        .split_once(':')
+        .map(|(scheme, _)| scheme.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match scheme.as_str() {
+        "file" => {
+            let path = reqwest::Url::parse(url)?
+                .to_file_path()
+                .map_err(|_| anyhow!("not a local file"))?;
+            read_file(&path)
+                .await
+                .with_context(|| format!("reading {}", path.display()))
+        }
+        "data" => decode_data_uri(url),
+        "http" | "https" => {
+            let response = HTTP_CLIENT.get(url).send().await?.error_for_status()?;
+            if let Some(len) = response.content_length() {
+                check_size(len)?;
+            }
+            // The length can be missing or wrong, so the body is checked as it arrives
+            let mut bytes = Vec::new();
+            let mut body = response.bytes_stream();
+            while let Some(chunk) = body.next().await {
+                let chunk = chunk?;
+                check_size((bytes.len() + chunk.len()) as u64)?;
+                bytes.extend_from_slice(&chunk);
+            }
+            Ok(bytes)
+        }
+        // Some players report a bare path instead of a file:// URL
+        _ if url.starts_with('/') => read_file(std::path::Path::new(url)).await,
+        _ => bail!("unsupported URL scheme"),
+    }
+}
+
+/// Reads a local cover, refusing oversized files up front
+async fn read_file(path: &std::path::Path) -> Result<Vec<u8>> {
+    check_size(tokio::fs::metadata(path).await?.len())?;
+    Ok(tokio::fs::read(path).await?)
+}
+
+/// Decodes `data:[<media type>][;base64],<data>`
+fn decode_data_uri(url: &str) -> Result<Vec<u8>> {
+    let (header, data) = url
+        .get("data:".len()..)
+        .and_then(|rest| rest.split_once(','))
+        .ok_or_else(|| anyhow!("malformed data URI"))?;
+
+    let data: Vec<u8> = percent_encoding::percent_decode_str(data).collect();
+    if header
+        .split(';')
+        .any(|parameter| parameter.trim().eq_ignore_ascii_case("base64"))
+    {
+        // Whitespace is allowed inside base64 data URIs
+        let data: Vec<u8> = data.into_iter().filter(|b| !b.is_ascii_whitespace()).collect();
+        Ok(base64::engine::general_purpose::STANDARD.decode(data)?)
+    } else {
+        Ok(data)
+    }
+}
+
+/// Keeps log lines short for data URIs, which hold the whole image
+fn preview(url: &str) -> &str {
+    url.char_indices().nth(80).map_or(url, |(end, _)| &url[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, RgbaImage};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbaImage::new(width, height)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn decodes_a_tiny_png() {
+        assert!(decode(&png(2, 2)).is_ok());
+    }
+
+    #[test]
+    fn decodes_and_scales_down_a_large_png() {
+        assert!(decode(&png(THUMBNAIL_SIZE * 3, THUMBNAIL_SIZE)).is_ok());
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(decode(b"definitely not an image").is_err());
+        assert!(decode(&[]).is_err());
+    }
+
+    #[test]
+    fn rejects_a_truncated_png() {
+        let bytes = png(16, 16);
+        assert!(decode(&bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn rejects_an_image_over_the_dimension_limit() {
+        assert!(decode(&png(MAX_ART_DIMENSION + 1, 1)).is_err());
+        assert!(decode(&png(1, MAX_ART_DIMENSION + 1)).is_err());
+    }
+
+    #[test]
+    fn rejects_covers_over_the_byte_limit() {
+        assert!(check_size(MAX_ART_BYTES).is_ok());
+        assert!(check_size(MAX_ART_BYTES + 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn download_without_length_stops_past_the_byte_limit() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/cover.png", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            // Read the request, or closing the socket would reset the connection
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match socket.read(&mut buffer).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(read) => request.extend_from_slice(&buffer[..read]),
+                }
+            }
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n")
+                .await;
+            let chunk = vec![0u8; 1024 * 1024];
+            for _ in 0..=MAX_ART_BYTES / chunk.len() as u64 {
+                if socket.write_all(&chunk).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let error = load_bytes(&url).await.unwrap_err();
+        assert!(format!("{:#}", error).contains("byte limit"), "{:#}", error);
+        server.abort();
+    }
+
+    #[test]
+    fn data_uri_with_base64() {
+        assert_eq!(
+            decode_data_uri("data:image/png;base64,aGVsbG8=").unwrap(),
+            b"hello"
+        );
+    }
+
+    #[test]
+    fn data_uri_base64_parameter_is_case_insensitive() {
+        assert_eq!(
+            decode_data_uri("data:image/png;BASE64,aGVsbG8=").unwrap(),
+            b"hello"
+        );
+    }
+
+    #[test]
+    fn data_uri_percent_encoded() {
+        assert_eq!(
+            decode_data_uri("data:text/plain,a%20b%2Cc").unwrap(),
+            b"a b,c"
+        );
+        assert_eq!(decode_data_uri("data:,%FF%00").unwrap(), [0xff, 0x00]);
+    }
+
+    #[test]
+    fn data_uri_percent_encoded_base64() {
+        assert_eq!(
+            decode_data_uri("data:;base64,aGVsbG8%3D").unwrap(),
+            b"hello"
+        );
+    }
+
+    #[test]
+    fn data_uri_base64_ignores_whitespace() {
+        assert_eq!(
+            decode_data_uri("data:image/png;base64,aGVs\n bG8=").unwrap(),
+            b"hello"
+        );
+        assert_eq!(
+            decode_data_uri("data:image/png;base64,aGVs%0AbG8=").unwrap(),
+            b"hello"
+        );
+    }
+
+    #[test]
+    fn data_uri_without_data() {
+        assert!(decode_data_uri("data:image/png;base64,")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn malformed_data_uris() {
+        assert!(decode_data_uri("data:image/png;base64").is_err());
+        assert!(decode_data_uri("data:").is_err());
+        assert!(decode_data_uri("data").is_err());
+        assert!(decode_data_uri("data:image/png;base64,not base64!").is_err());
+    }
+}
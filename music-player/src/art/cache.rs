@@ -0,0 +1,231 @@
+//! Album art caches: downloaded covers on disk, and recently shown images in memory.
+//!
+//! Disk entries are named after a hash of their URL and their modification time is
+//! bumped on every hit, so evicting the oldest files drops the least recently used ones.
+
+use crate::config::CONFIG_ID;
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Directory the covers are kept in, `$XDG_CACHE_HOME/com.cosmic.MusicPlayer/art`
+pub fn cache_dir() -> Option<PathBuf> {
+    let cache_home = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(cache_home.join(CONFIG_ID).join("art"))
+}
+
+fn entry_path(dir: &Path, url: &str) -> PathBuf {
+    let digest = Sha256::digest(url.as_bytes());
+    let name: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    dir.join(name)
+}
+
+/// The cached cover for `url`, marking it as recently used
+pub async fn read(url: &str) -> Option<Vec<u8>> {
+    let path = entry_path(&cache_dir()?, url);
+    let bytes = tokio::fs::read(&path).await.ok()?;
+
+    let _ = tokio::task::spawn_blocking(move || {
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+    })
+    .await;
+
+    Some(bytes)
+}
+
+/// Stores the cover for `url`, then trims the cache to `limit_bytes`
+pub async fn write(url: &str, bytes: &[u8], limit_bytes: u64) -> Result<()> {
+    let Some(dir) = cache_dir() else {
+        return Ok(());
+    };
+    // Covers bigger than the whole cache are not worth keeping
+    if bytes.len() as u64 > limit_bytes {
+        return Ok(());
+    }
+
+    tokio::fs::create_dir_all(&dir).await?;
+    let path = entry_path(&dir, url);
+
+    // Written next to the entry and renamed, so readers never see half a file
+    let partial = path.with_extension("part");
+    tokio::fs::write(&partial, bytes).await?;
+    tokio::fs::rename(&partial, &path).await?;
+
+    tokio::task::spawn_blocking(move || evict(&dir, limit_bytes)).await?
+}
+
+/// Trims the cache to `limit_bytes`, e.g. after the limit was lowered
+pub async fn trim(limit_bytes: u64) -> Result<()> {
+    let Some(dir) = cache_dir() else {
+        return Ok(());
+    };
+    if !tokio::fs::try_exists(&dir).await? {
+        return Ok(());
+    }
+    tokio::task::spawn_blocking(move || evict(&dir, limit_bytes)).await?
+}
+
+/// Removes the least recently used entries until the cache fits in `limit_bytes`
+pub fn evict(dir: &Path, limit_bytes: u64) -> Result<()> {
+    let mut entries: Vec<(SystemTime, u64, PathBuf)> = std::fs::read_dir(dir)?
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then(|| {
+                (
+                    metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    metadata.len(),
+                    entry.path(),
+                )
+            })
+        })
+        .collect();
+
+    let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    entries.sort_by_key(|(modified, _, _)| *modified);
+
+    for (_, size, path) in entries {
+        if total <= limit_bytes {
+            break;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+
+    Ok(())
+}
+
+/// The most recently used values by URL, e.g. image handles that are already decoded
+#[derive(Debug)]
+pub struct MemoryCache<T> {
+    capacity: usize,
+    /// Least recently used first
+    entries: VecDeque<(String, T)>,
+}
+
+impl<T: Clone> MemoryCache<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn get(&mut self, url: &str) -> Option<T> {
+        let index = self.entries.iter().position(|(key, _)| key == url)?;
+        let entry = self.entries.remove(index)?;
+        let value = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(value)
+    }
+
+    pub fn insert(&mut self, url: String, value: T) {
+        self.entries.retain(|(key, _)| *key != url);
+        self.entries.push_back((url, value));
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// A fresh directory with one 10 byte entry per name, the first one least recently used
+    fn cache_with_entries(test: &str, names: &[&str]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("music-player-{}-{}", test, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (age, name) in names.iter().enumerate() {
+            let path = dir.join(name);
+            std::fs::write(&path, [0u8; 10]).unwrap();
+            let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000 + age as u64);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(modified))
+                .unwrap();
+        }
+        dir
+    }
+
+    fn remaining(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn evict_drops_least_recently_used_first() {
+        let dir = cache_with_entries("evict-lru", &["a", "b", "c"]);
+        evict(&dir, 20).unwrap();
+        assert_eq!(remaining(&dir), ["b", "c"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn evict_keeps_a_cache_within_the_limit() {
+        let dir = cache_with_entries("evict-within", &["a", "b", "c"]);
+        evict(&dir, 30).unwrap();
+        assert_eq!(remaining(&dir), ["a", "b", "c"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn evict_to_zero_empties_the_cache() {
+        let dir = cache_with_entries("evict-zero", &["a", "b"]);
+        evict(&dir, 0).unwrap();
+        assert!(remaining(&dir).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn memory_cache_drops_the_oldest_entry_past_capacity() {
+        let mut cache = MemoryCache::new(2);
+        cache.insert("a".to_string(), 1);
+        cache.insert("b".to_string(), 2);
+        cache.insert("c".to_string(), 3);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(2));
+        assert_eq!(cache.get("c"), Some(3));
+    }
+
+    #[test]
+    fn memory_cache_get_marks_an_entry_as_recently_used() {
+        let mut cache = MemoryCache::new(2);
+        cache.insert("a".to_string(), 1);
+        cache.insert("b".to_string(), 2);
+        assert_eq!(cache.get("a"), Some(1));
+        cache.insert("c".to_string(), 3);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("b"), None);
+    }
+
+    #[test]
+    fn memory_cache_insert_replaces_an_existing_entry() {
+        let mut cache = MemoryCache::new(2);
+        cache.insert("a".to_string(), 1);
+        cache.insert("b".to_string(), 2);
+        cache.insert("a".to_string(), 3);
+        cache.insert("c".to_string(), 4);
+        assert_eq!(cache.get("a"), Some(3));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c"), Some(4));
+    }
+}
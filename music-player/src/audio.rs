@@ -1,24 +1,139 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::process::Command;
 use std::sync::{Arc, Mutex};
 
+mod activity;
+mod pactl;
+
+use activity::StreamActivity;
+
+/// Raw PulseAudio volume that corresponds to 100% (`PA_VOLUME_NORM`)
+const VOLUME_NORM: f64 = 65536.0;
+
+/// How far the ducking factor moves per update when lowering music
+const DUCK_STEP: f64 = 0.25;
+/// How far the ducking factor moves per update when restoring music
+const RESTORE_STEP: f64 = 0.1;
+
 #[derive(Debug, Clone)]
 pub struct AudioSinkInput {
     pub index: u32,
     pub application_name: String,
     pub media_name: String,
+    /// Loudest channel, used as the overall stream volume
     pub volume: f64,
+    /// Channel positions as reported by pactl (e.g. "front-left")
+    pub channel_map: Vec<String>,
+    /// Volume of each channel, in the same order as `channel_map`
+    pub channel_volumes: Vec<f64>,
+    /// Paused streams are corked and do not produce sound
+    pub corked: bool,
+}
+
+impl AudioSinkInput {
+    /// Stereo balance from -1.0 (fully left) to 1.0 (fully right)
+    pub fn balance(&self) -> f64 {
+        let (left, right) = self.side_volumes();
+        let max = left.max(right);
+        if max <= 0.0 {
+            return 0.0;
+        }
+        if left >= right {
+            right / max - 1.0
+        } else {
+            1.0 - left / max
+        }
+    }
+
+    fn side_volumes(&self) -> (f64, f64) {
+        let mut left: f64 = 0.0;
+        let mut right: f64 = 0.0;
+        for (position, volume) in self.channel_map.iter().zip(&self.channel_volumes) {
+            match channel_side(position) {
+                Some(ChannelSide::Left) => left = left.max(*volume),
+                Some(ChannelSide::Right) => right = right.max(*volume),
+                None => {}
+            }
+        }
+        (left, right)
+    }
+}
+
+/// An output device (sink)
+#[derive(Debug, Clone)]
+pub struct AudioSink {
+    pub index: u32,
+    pub name: String,
+    pub description: String,
+    /// Loudest channel, used as the overall sink volume
+    pub volume: f64,
+    pub channel_volumes: Vec<f64>,
+    pub muted: bool,
+}
+
+/// Music streams lowered while other audio plays
+struct DuckingState {
+    /// Current multiplier applied to the original volumes
+    factor: f64,
+    streams: HashMap<u32, DuckedStream>,
+}
+
+impl Default for DuckingState {
+    fn default() -> Self {
+        Self {
+            factor: 1.0,
+            streams: HashMap::new(),
+        }
+    }
+}
+
+struct DuckedStream {
+    original_volume: f64,
+    applied_volume: f64,
+}
+
+/// Output of a `pactl list` call, in whichever format pactl supports
+enum PactlListing {
+    Json(String),
+    Text(String),
+}
+
+enum ChannelSide {
+    Left,
+    Right,
+}
+
+fn channel_side(position: &str) -> Option<ChannelSide> {
+    if position.ends_with("left") {
+        Some(ChannelSide::Left)
+    } else if position.ends_with("right") {
+        Some(ChannelSide::Right)
+    } else {
+        None
+    }
 }
 
 pub struct AudioController {
     sink_inputs: Arc<Mutex<HashMap<u32, AudioSinkInput>>>,
+    sinks: Arc<Mutex<Vec<AudioSink>>>,
+    default_sink: Arc<Mutex<Option<String>>>,
+    ducking: Arc<Mutex<DuckingState>>,
+    /// Whether the streams ducking reacts to actually make sound
+    activity: Arc<Mutex<StreamActivity>>,
+    /// Whether pactl understands `--format=json`, `None` until first tried
+    json_supported: Arc<Mutex<Option<bool>>>,
 }
 
 impl AudioController {
     pub fn new() -> Result<Self> {
         Ok(Self {
             sink_inputs: Arc::new(Mutex::new(HashMap::new())),
+            sinks: Arc::new(Mutex::new(Vec::new())),
+            default_sink: Arc::new(Mutex::new(None)),
+            ducking: Arc::new(Mutex::new(DuckingState::default())),
+            activity: Arc::new(Mutex::new(StreamActivity::default())),
+            json_supported: Arc::new(Mutex::new(None)),
         })
     }
 
@@ -29,85 +144,16 @@ impl AudioController {
 
     pub fn refresh_sink_inputs(&self) -> Result<()> {
         // Use pactl to list sink inputs
-        let output = Command::new("pactl")
-            .arg("list")
-            .arg("sink-inputs")
-            .output()?;
-
-        if !output.status.success() {
-            return Err(anyhow::anyhow!("pactl command failed"));
-        }
+        let parsed = match self.pactl_list("sink-inputs")? {
+            PactlListing::Json(output) => self.parse_json(pactl::parse_sink_inputs_json(&output))?,
+            PactlListing::Text(output) => pactl::parse_sink_inputs_text(&output),
+        };
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
         let mut sink_inputs = self.sink_inputs.lock().unwrap();
-        sink_inputs.clear();
-
-        let mut current_index: Option<u32> = None;
-        let mut current_app_name = String::new();
-        let mut current_media_name = String::new();
-        let mut current_volume = 1.0;
-
-        for line in output_str.lines() {
-            let line = line.trim();
-
-            if line.starts_with("Sink Input #") {
-                // Save previous entry if exists
-                if let Some(index) = current_index {
-                    sink_inputs.insert(
-                        index,
-                        AudioSinkInput {
-                            index,
-                            application_name: current_app_name.clone(),
-                            media_name: current_media_name.clone(),
-                            volume: current_volume,
-                        },
-                    );
-                }
-
-                // Start new entry
-                if let Some(index_str) = line.strip_prefix("Sink Input #") {
-                    current_index = index_str.parse().ok();
-                    current_app_name = String::new();
-                    current_media_name = String::new();
-                    current_volume = 1.0;
-                }
-            } else if line.starts_with("application.name = ") {
-                current_app_name = line
-                    .strip_prefix("application.name = \"")
-                    .and_then(|s| s.strip_suffix("\""))
-                    .unwrap_or("")
-                    .to_string();
-            } else if line.starts_with("media.name = ") {
-                current_media_name = line
-                    .strip_prefix("media.name = \"")
-                    .and_then(|s| s.strip_suffix("\""))
-                    .unwrap_or("")
-                    .to_string();
-            } else if line.starts_with("Volume:") {
-                // Parse volume percentage (e.g., "Volume: front-left: 65536 / 100%")
-                if let Some(percent_pos) = line.find('%') {
-                    let before_percent = &line[..percent_pos];
-                    if let Some(last_space) = before_percent.rfind(' ') {
-                        if let Ok(percent) = before_percent[last_space + 1..].trim().parse::<f64>() {
-                            current_volume = percent / 100.0;
-                        }
-                    }
-                }
-            }
-        }
-
-        // Save last entry
-        if let Some(index) = current_index {
-            sink_inputs.insert(
-                index,
-                AudioSinkInput {
-                    index,
-                    application_name: current_app_name,
-                    media_name: current_media_name,
-                    volume: current_volume,
-                },
-            );
-        }
+        *sink_inputs = parsed
+            .into_iter()
+            .map(|sink_input| (sink_input.index, sink_input))
+            .collect();
 
         Ok(())
     }
@@ -131,31 +177,377 @@ impl AudioController {
         // Clamp volume to 0.0-1.5 (150%)
         let clamped_volume = volume.clamp(0.0, 1.5);
 
-        // Convert to percentage
-        let volume_percent = (clamped_volume * 100.0) as u32;
+        // Scale every channel proportionally so the balance is preserved
+        let channel_volumes = match self.get_sink_input(index) {
+            Some(sink_input) if sink_input.volume > 0.0 => sink_input
+                .channel_volumes
+                .iter()
+                .map(|v| v / sink_input.volume * clamped_volume)
+                .collect(),
+            Some(sink_input) if !sink_input.channel_volumes.is_empty() => {
+                vec![clamped_volume; sink_input.channel_volumes.len()]
+            }
+            _ => vec![clamped_volume],
+        };
+
+        self.set_channel_volumes(index, &channel_volumes)
+    }
+
+    /// Sets the stereo balance (-1.0 left to 1.0 right) keeping the overall volume
+    pub fn set_sink_input_balance(&self, index: u32, balance: f64) -> Result<()> {
+        let Some(sink_input) = self.get_sink_input(index) else {
+            return Err(anyhow::anyhow!("Sink input {} not found", index));
+        };
+
+        let balance = balance.clamp(-1.0, 1.0);
+        let left_factor = if balance > 0.0 { 1.0 - balance } else { 1.0 };
+        let right_factor = if balance < 0.0 { 1.0 + balance } else { 1.0 };
+
+        let channel_volumes: Vec<f64> = sink_input
+            .channel_map
+            .iter()
+            .map(|position| match channel_side(position) {
+                Some(ChannelSide::Left) => sink_input.volume * left_factor,
+                Some(ChannelSide::Right) => sink_input.volume * right_factor,
+                None => sink_input.volume,
+            })
+            .collect();
+
+        if channel_volumes.is_empty() {
+            return Ok(());
+        }
 
-        // Use pactl to set volume
+        self.set_channel_volumes(index, &channel_volumes)
+    }
+
+    fn set_channel_volumes(&self, index: u32, channel_volumes: &[f64]) -> Result<()> {
+        // Use pactl to set volume, one raw value per channel
         let output = Command::new("pactl")
             .arg("set-sink-input-volume")
             .arg(index.to_string())
-            .arg(format!("{}%", volume_percent))
+            .args(
+                channel_volumes
+                    .iter()
+                    .map(|v| ((v.clamp(0.0, 1.5) * VOLUME_NORM).round() as u32).to_string()),
+            )
             .output()?;
 
         if !output.status.success() {
             return Err(anyhow::anyhow!("pactl set-sink-input-volume failed"));
         }
 
+        // Keep the cached state in sync so follow-up changes scale from the new values
+        if let Some(sink_input) = self.sink_inputs.lock().unwrap().get_mut(&index) {
+            if sink_input.channel_volumes.len() == channel_volumes.len() {
+                sink_input.channel_volumes = channel_volumes.to_vec();
+                sink_input.volume = channel_volumes.iter().copied().fold(0.0, f64::max);
+            }
+        }
+
         Ok(())
     }
 
+    pub fn get_sink_input(&self, index: u32) -> Option<AudioSinkInput> {
+        let sink_inputs = self.sink_inputs.lock().unwrap();
+        sink_inputs.get(&index).cloned()
+    }
+
     pub fn get_all_sink_inputs(&self) -> Vec<AudioSinkInput> {
         let sink_inputs = self.sink_inputs.lock().unwrap();
         sink_inputs.values().cloned().collect()
     }
+
+    pub fn refresh_sinks(&self) -> Result<()> {
+        // Use pactl to list output devices
+        let sinks = match self.pactl_list("sinks")? {
+            PactlListing::Json(output) => self.parse_json(pactl::parse_sinks_json(&output))?,
+            PactlListing::Text(output) => pactl::parse_sinks_text(&output),
+        };
+
+        *self.sinks.lock().unwrap() = sinks;
+
+        // Remember which sink is the default output
+        let output = Command::new("pactl").arg("get-default-sink").output()?;
+        if output.status.success() {
+            let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            *self.default_sink.lock().unwrap() = Some(name).filter(|n| !n.is_empty());
+        }
+
+        Ok(())
+    }
+
+    pub fn get_sinks(&self) -> Vec<AudioSink> {
+        self.sinks.lock().unwrap().clone()
+    }
+
+    pub fn get_default_sink(&self) -> Option<AudioSink> {
+        let default_name = self.default_sink.lock().unwrap().clone()?;
+        self.sinks
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|sink| sink.name == default_name)
+            .cloned()
+    }
+
+    /// Sets the default output volume, scaling channels proportionally
+    pub fn set_default_sink_volume(&self, volume: f64) -> Result<()> {
+        let clamped_volume = volume.clamp(0.0, 1.5);
+
+        let channel_volumes: Vec<f64> = match self.get_default_sink() {
+            Some(sink) if sink.volume > 0.0 => sink
+                .channel_volumes
+                .iter()
+                .map(|v| v / sink.volume * clamped_volume)
+                .collect(),
+            _ => vec![clamped_volume],
+        };
+
+        let output = Command::new("pactl")
+            .arg("set-sink-volume")
+            .arg("@DEFAULT_SINK@")
+            .args(
+                channel_volumes
+                    .iter()
+                    .map(|v| ((v * VOLUME_NORM).round() as u32).to_string()),
+            )
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("pactl set-sink-volume failed"));
+        }
+
+        Ok(())
+    }
+
+    pub fn set_default_sink_mute(&self, muted: bool) -> Result<()> {
+        let output = Command::new("pactl")
+            .arg("set-sink-mute")
+            .arg("@DEFAULT_SINK@")
+            .arg(if muted { "1" } else { "0" })
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("pactl set-sink-mute failed"));
+        }
+
+        Ok(())
+    }
+
+    /// Moves a stream to another output
+    pub fn move_sink_input(&self, index: u32, sink_name: &str) -> Result<()> {
+        let output = Command::new("pactl")
+            .arg("move-sink-input")
+            .arg(index.to_string())
+            .arg(sink_name)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("pactl move-sink-input failed"));
+        }
+
+        Ok(())
+    }
+
+    pub fn set_default_sink(&self, name: &str) -> Result<()> {
+        let output = Command::new("pactl")
+            .arg("set-default-sink")
+            .arg(name)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("pactl set-default-sink failed"));
+        }
+
+        *self.default_sink.lock().unwrap() = Some(name.to_string());
+
+        Ok(())
+    }
+
+    /// Runs `pactl list <kind>`, preferring JSON output and falling back to
+    /// untranslated text for pactl versions without `--format=json`
+    fn pactl_list(&self, kind: &str) -> Result<PactlListing> {
+        if *self.json_supported.lock().unwrap() != Some(false) {
+            let output = Command::new("pactl")
+                .arg("--format=json")
+                .arg("list")
+                .arg(kind)
+                .output()?;
+
+            if output.status.success() {
+                *self.json_supported.lock().unwrap() = Some(true);
+                return Ok(PactlListing::Json(
+                    String::from_utf8_lossy(&output.stdout).into_owned(),
+                ));
+            }
+            *self.json_supported.lock().unwrap() = Some(false);
+        }
+
+        let output = Command::new("pactl")
+            .env("LC_ALL", "C")
+            .arg("list")
+            .arg(kind)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("pactl command failed"));
+        }
+
+        Ok(PactlListing::Text(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    /// Falls back to the text format next time if pactl produced JSON we cannot read
+    fn parse_json<T>(&self, parsed: Result<T>) -> Result<T> {
+        if parsed.is_err() {
+            *self.json_supported.lock().unwrap() = Some(false);
+        }
+        parsed
+    }
+
+    /// Lowers `music_streams` by `amount` (0.0-1.0) while any other stream makes sound
+    /// (or, optionally, something records from a microphone), and restores them step by
+    /// step afterwards. Meant to be called periodically after `refresh_sink_inputs`.
+    pub fn update_ducking(
+        &self,
+        music_streams: &HashSet<u32>,
+        amount: f64,
+        duck_on_recording: bool,
+    ) -> Result<()> {
+        // Only uncorked streams can make sound, the rest are not worth monitoring
+        let other_streams: HashSet<u32> = if amount > 0.0 {
+            self.get_all_sink_inputs()
+                .iter()
+                .filter(|sink_input| {
+                    !sink_input.corked && !music_streams.contains(&sink_input.index)
+                })
+                .map(|sink_input| sink_input.index)
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        let other_audible = {
+            let mut activity = self.activity.lock().unwrap();
+            activity.sync(&other_streams);
+            activity.any_audible()
+        };
+
+        let other_active = amount > 0.0
+            && (other_audible || (duck_on_recording && self.is_recording().unwrap_or(false)));
+        let target = if other_active {
+            1.0 - amount.clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        let mut ducking = self.ducking.lock().unwrap();
+        if ducking.factor >= 1.0 && target >= 1.0 {
+            ducking.streams.clear();
+            return Ok(());
+        }
+
+        // Duck quickly, restore smoothly
+        ducking.factor = if target < ducking.factor {
+            (ducking.factor - DUCK_STEP).max(target)
+        } else {
+            (ducking.factor + RESTORE_STEP).min(target)
+        };
+        let factor = ducking.factor;
+
+        ducking.streams.retain(|index, _| music_streams.contains(index));
+
+        for index in music_streams {
+            let Some(sink_input) = self.get_sink_input(*index) else {
+                continue;
+            };
+
+            let stream = ducking.streams.entry(*index).or_insert(DuckedStream {
+                original_volume: sink_input.volume,
+                applied_volume: sink_input.volume,
+            });
+
+            // The user changed the volume while ducked, treat it as the new original
+            if (sink_input.volume - stream.applied_volume).abs() > 0.02 {
+                stream.original_volume = sink_input.volume;
+            }
+
+            let volume = stream.original_volume * factor;
+            if (volume - sink_input.volume).abs() > 0.005 {
+                self.set_sink_input_volume(*index, volume)?;
+            }
+            stream.applied_volume = volume;
+        }
+
+        if factor >= 1.0 {
+            ducking.streams.clear();
+        }
+
+        Ok(())
+    }
+
+    /// Puts ducked streams back at their original volume, e.g. before exiting
+    pub fn restore_ducking(&self) {
+        let mut ducking = self.ducking.lock().unwrap();
+        for (index, stream) in ducking.streams.drain() {
+            let _ = self.set_sink_input_volume(index, stream.original_volume);
+        }
+        ducking.factor = 1.0;
+        self.activity.lock().unwrap().sync(&HashSet::new());
+    }
+
+    pub fn is_ducking(&self) -> bool {
+        self.ducking.lock().unwrap().factor < 1.0
+    }
+
+    /// Whether any application records from a real input (monitors are ignored)
+    fn is_recording(&self) -> Result<bool> {
+        let output = Command::new("pactl")
+            .arg("list")
+            .arg("short")
+            .arg("source-outputs")
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("pactl command failed"));
+        }
+
+        // Columns: index, source index, client, driver, sample spec
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        let recorded_sources: HashSet<&str> = output_str
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .collect();
+
+        if recorded_sources.is_empty() {
+            return Ok(false);
+        }
+
+        let output = Command::new("pactl")
+            .arg("list")
+            .arg("short")
+            .arg("sources")
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("pactl command failed"));
+        }
+
+        // Columns: index, name, driver, sample spec, state
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        Ok(output_str.lines().any(|line| {
+            let mut columns = line.split_whitespace();
+            match (columns.next(), columns.next()) {
+                (Some(index), Some(name)) => {
+                    recorded_sources.contains(index) && !name.ends_with(".monitor")
+                }
+                _ => false,
+            }
+        }))
+    }
 }
 
 impl Drop for AudioController {
     fn drop(&mut self) {
-        // No cleanup needed for pactl-based approach
+        // Do not leave music lowered behind
+        self.restore_ducking();
     }
 }
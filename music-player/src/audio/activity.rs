@@ -0,0 +1,174 @@
+//! Tells audible streams apart from idle ones.
+//!
+//! Browsers and voice chat applications keep uncorked streams open while playing nothing,
+//! so a stream only counts as playing once its monitor actually carries sound.
+
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Plenty to tell sound from silence
+const SAMPLE_RATE: u32 = 4000;
+/// Samples are read and checked in chunks of this many (100ms)
+const CHUNK_SAMPLES: usize = 400;
+/// Peaks below this (about -50 dB) are silence
+const SILENCE_PEAK: u16 = 100;
+/// A stream stays audible this long after its last sound, so short pauses (e.g. between
+/// sentences in a call) do not restore the music
+const AUDIBLE_HOLD: Duration = Duration::from_secs(3);
+
+/// Records the monitor of one stream and remembers when it last made a sound
+struct StreamMonitor {
+    child: Child,
+    last_audible: Arc<Mutex<Option<Instant>>>,
+}
+
+impl StreamMonitor {
+    fn start(stream_index: u32) -> Result<Self> {
+        let mut child = Command::new("parec")
+            .arg(format!("--monitor-stream={}", stream_index))
+            .arg("--format=s16le")
+            .arg("--channels=1")
+            .arg(format!("--rate={}", SAMPLE_RATE))
+            .arg("--raw")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("parec has no stdout"))?;
+        let last_audible = Arc::new(Mutex::new(None));
+        let thread_last_audible = last_audible.clone();
+
+        // The thread ends on its own once the child is killed and stdout closes
+        thread::spawn(move || {
+            let mut buffer = vec![0u8; CHUNK_SAMPLES * 2];
+            while stdout.read_exact(&mut buffer).is_ok() {
+                if peak(&buffer) < SILENCE_PEAK {
+                    continue;
+                }
+                let Ok(mut last_audible) = thread_last_audible.lock() else {
+                    break;
+                };
+                *last_audible = Some(Instant::now());
+            }
+        });
+
+        Ok(Self {
+            child,
+            last_audible,
+        })
+    }
+
+    fn is_audible(&self) -> bool {
+        self.last_audible
+            .lock()
+            .ok()
+            .and_then(|last_audible| *last_audible)
+            .is_some_and(|last_audible| last_audible.elapsed() < AUDIBLE_HOLD)
+    }
+}
+
+impl Drop for StreamMonitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Monitors of the streams ducking reacts to, keyed by sink input index
+#[derive(Default)]
+pub struct StreamActivity {
+    /// `None` for streams that could not be monitored, those count as silent so an idle
+    /// stream cannot keep the music lowered (e.g. when `parec` is missing)
+    monitors: HashMap<u32, Option<StreamMonitor>>,
+    /// A failure to monitor was logged, later ones are not
+    failure_logged: bool,
+}
+
+impl StreamActivity {
+    /// Monitors exactly `streams`, starting and stopping captures as needed
+    pub fn sync(&mut self, streams: &HashSet<u32>) {
+        self.monitors.retain(|index, _| streams.contains(index));
+        for index in streams {
+            if self.monitors.contains_key(index) {
+                continue;
+            }
+            let monitor = match StreamMonitor::start(*index) {
+                Ok(monitor) => Some(monitor),
+                Err(e) => {
+                    if !std::mem::replace(&mut self.failure_logged, true) {
+                        eprintln!(
+                            "Failed to monitor stream {}, streams that cannot be monitored do not duck music: {}",
+                            index, e
+                        );
+                    }
+                    None
+                }
+            };
+            self.monitors.insert(*index, monitor);
+        }
+    }
+
+    pub fn any_audible(&self) -> bool {
+        self.monitors
+            .values()
+            .flatten()
+            .any(|monitor| monitor.is_audible())
+    }
+}
+
+/// Loudest sample of raw s16le audio
+fn peak(samples: &[u8]) -> u16 {
+    samples
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]).unsigned_abs())
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_monitored_is_silent() {
+        assert!(!StreamActivity::default().any_audible());
+    }
+
+    #[test]
+    fn streams_that_cannot_be_monitored_are_silent() {
+        let activity = StreamActivity {
+            monitors: HashMap::from([(7, None), (8, None)]),
+            failure_logged: true,
+        };
+        assert!(!activity.any_audible());
+    }
+
+    #[test]
+    fn sync_forgets_streams_that_went_away() {
+        let mut activity = StreamActivity {
+            monitors: HashMap::from([(7, None), (8, None)]),
+            failure_logged: true,
+        };
+        activity.sync(&HashSet::new());
+        assert!(activity.monitors.is_empty());
+    }
+
+    #[test]
+    fn peak_of_samples() {
+        let samples: Vec<u8> = [0i16, -300, 200, i16::MIN]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        assert_eq!(peak(&samples), 32768);
+        assert_eq!(peak(&samples[..6]), 300);
+        assert_eq!(peak(&[]), 0);
+    }
+}
@@ -0,0 +1,336 @@
+//! Parsing of `pactl list` output, kept free of I/O so it can be tested with fixtures.
+//!
+//! `pactl --format=json` (PulseAudio 16+ and pipewire-pulse) is preferred. The text
+//! format is only a fallback and must be produced with `LC_ALL=C`, since its labels
+//! ("Sink Input #", "Volume:", ...) are translated.
+
+use super::{AudioSink, AudioSinkInput, VOLUME_NORM};
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+struct JsonSinkInput {
+    index: u32,
+    #[serde(default)]
+    corked: bool,
+    #[serde(default)]
+    channel_map: String,
+    #[serde(default)]
+    volume: HashMap<String, JsonChannelVolume>,
+    #[serde(default)]
+    properties: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct JsonSink {
+    index: u32,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    mute: bool,
+    #[serde(default)]
+    channel_map: String,
+    #[serde(default)]
+    volume: HashMap<String, JsonChannelVolume>,
+}
+
+#[derive(Deserialize)]
+struct JsonChannelVolume {
+    value: u32,
+}
+
+pub fn parse_sink_inputs_json(output: &str) -> Result<Vec<AudioSinkInput>> {
+    let entries: Vec<JsonSinkInput> = serde_json::from_str(output)?;
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let property = |key: &str| {
+                entry
+                    .properties
+                    .get(key)
+                    .and_then(|value| value.as_str())
+                    .unwrap_or("")
+                    .to_string()
+            };
+            let (channel_map, channel_volumes) = json_channels(&entry.channel_map, &entry.volume);
+
+            AudioSinkInput {
+                index: entry.index,
+                application_name: property("application.name"),
+                media_name: property("media.name"),
+                volume: max_volume(&channel_volumes),
+                channel_map,
+                channel_volumes,
+                corked: entry.corked,
+            }
+        })
+        .collect())
+}
+
+pub fn parse_sinks_json(output: &str) -> Result<Vec<AudioSink>> {
+    let entries: Vec<JsonSink> = serde_json::from_str(output)?;
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let (_, channel_volumes) = json_channels(&entry.channel_map, &entry.volume);
+
+            AudioSink {
+                index: entry.index,
+                name: entry.name,
+                description: entry.description,
+                volume: max_volume(&channel_volumes),
+                channel_volumes,
+                muted: entry.mute,
+            }
+        })
+        .collect())
+}
+
+/// JSON volumes are an object keyed by channel position, so the order comes from `channel_map`
+fn json_channels(
+    channel_map: &str,
+    volume: &HashMap<String, JsonChannelVolume>,
+) -> (Vec<String>, Vec<f64>) {
+    let mut positions: Vec<String> = channel_map
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if positions.is_empty() {
+        positions = volume.keys().cloned().collect();
+        positions.sort();
+    }
+
+    let channel_volumes = positions
+        .iter()
+        .map(|position| {
+            volume
+                .get(position)
+                .map(|v| f64::from(v.value) / VOLUME_NORM)
+                .unwrap_or(1.0)
+        })
+        .collect();
+
+    (positions, channel_volumes)
+}
+
+/// Parses `LC_ALL=C pactl list sink-inputs`
+pub fn parse_sink_inputs_text(output: &str) -> Vec<AudioSinkInput> {
+    let mut sink_inputs: Vec<AudioSinkInput> = Vec::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+
+        if let Some(index_str) = line.strip_prefix("Sink Input #") {
+            // Start new entry
+            if let Ok(index) = index_str.trim().parse() {
+                sink_inputs.push(AudioSinkInput {
+                    index,
+                    application_name: String::new(),
+                    media_name: String::new(),
+                    volume: 1.0,
+                    channel_map: Vec::new(),
+                    channel_volumes: Vec::new(),
+                    corked: false,
+                });
+            }
+            continue;
+        }
+
+        let Some(sink_input) = sink_inputs.last_mut() else {
+            continue;
+        };
+
+        if let Some(volume_str) = line.strip_prefix("Volume:") {
+            // Parse every channel (e.g., "Volume: front-left: 65536 / 100% / 0.00 dB, ...")
+            let channels = parse_channel_volumes(volume_str);
+            sink_input.channel_volumes = channels.iter().map(|(_, volume)| *volume).collect();
+            sink_input.volume = max_volume(&sink_input.channel_volumes);
+            if sink_input.channel_map.is_empty() {
+                sink_input.channel_map =
+                    channels.into_iter().map(|(position, _)| position).collect();
+            }
+        } else if let Some(map_str) = line.strip_prefix("Channel Map:") {
+            sink_input.channel_map = map_str.split(',').map(|s| s.trim().to_string()).collect();
+        } else if let Some(corked) = line.strip_prefix("Corked:") {
+            sink_input.corked = corked.trim() == "yes";
+        } else if let Some((key, value)) = parse_property(line) {
+            match key {
+                "application.name" => sink_input.application_name = value,
+                "media.name" => sink_input.media_name = value,
+                _ => {}
+            }
+        }
+    }
+
+    sink_inputs
+}
+
+/// Parses `LC_ALL=C pactl list sinks`
+pub fn parse_sinks_text(output: &str) -> Vec<AudioSink> {
+    let mut sinks: Vec<AudioSink> = Vec::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+
+        if let Some(index_str) = line.strip_prefix("Sink #") {
+            // Start new entry
+            if let Ok(index) = index_str.trim().parse() {
+                sinks.push(AudioSink {
+                    index,
+                    name: String::new(),
+                    description: String::new(),
+                    volume: 1.0,
+                    channel_volumes: Vec::new(),
+                    muted: false,
+                });
+            }
+        } else if let Some(sink) = sinks.last_mut() {
+            if let Some(name) = line.strip_prefix("Name:") {
+                sink.name = name.trim().to_string();
+            } else if let Some(description) = line.strip_prefix("Description:") {
+                sink.description = description.trim().to_string();
+            } else if let Some(mute) = line.strip_prefix("Mute:") {
+                sink.muted = mute.trim() == "yes";
+            } else if let Some(volume_str) = line.strip_prefix("Volume:") {
+                sink.channel_volumes = parse_channel_volumes(volume_str)
+                    .into_iter()
+                    .map(|(_, volume)| volume)
+                    .collect();
+                sink.volume = max_volume(&sink.channel_volumes);
+            }
+        }
+    }
+
+    sinks
+}
+
+/// Parses a pactl volume list like
+/// "front-left: 65536 / 100% / 0.00 dB,   front-right: 32768 /  50% / -18.06 dB"
+fn parse_channel_volumes(volume_str: &str) -> Vec<(String, f64)> {
+    volume_str
+        .split(',')
+        .filter_map(|channel| {
+            let (position, values) = channel.split_once(':')?;
+            let raw = values.split('/').next()?.trim().parse::<f64>().ok()?;
+            Some((position.trim().to_string(), raw / VOLUME_NORM))
+        })
+        .collect()
+}
+
+/// Parses a property line like `media.name = "Say \"Hi\""` into its key and unescaped value
+fn parse_property(line: &str) -> Option<(&str, String)> {
+    let (key, value) = line.split_once(" = ")?;
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                unescaped.push(escaped);
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+
+    Some((key.trim(), unescaped))
+}
+
+fn max_volume(channel_volumes: &[f64]) -> f64 {
+    if channel_volumes.is_empty() {
+        1.0
+    } else {
+        channel_volumes.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SINK_INPUTS_TEXT: &str = include_str!("fixtures/sink_inputs.txt");
+    const SINK_INPUTS_JSON: &str = include_str!("fixtures/sink_inputs.json");
+    const SINKS_TEXT: &str = include_str!("fixtures/sinks.txt");
+    const SINKS_JSON: &str = include_str!("fixtures/sinks.json");
+
+    #[test]
+    fn parses_sink_inputs_text() {
+        let sink_inputs = parse_sink_inputs_text(SINK_INPUTS_TEXT);
+        assert_eq!(sink_inputs.len(), 2);
+
+        let firefox = &sink_inputs[0];
+        assert_eq!(firefox.index, 42);
+        assert_eq!(firefox.application_name, "Firefox");
+        assert_eq!(firefox.media_name, "Song \"Live\" - YouTube");
+        assert_eq!(firefox.channel_map, ["front-left", "front-right"]);
+        assert_eq!(firefox.channel_volumes, [1.0, 0.5]);
+        assert_eq!(firefox.volume, 1.0);
+        assert!(!firefox.corked);
+
+        let spotify = &sink_inputs[1];
+        assert_eq!(spotify.index, 57);
+        assert_eq!(spotify.application_name, "spotify");
+        assert!(spotify.corked);
+    }
+
+    #[test]
+    fn parses_sink_inputs_json() {
+        let sink_inputs = parse_sink_inputs_json(SINK_INPUTS_JSON).unwrap();
+        assert_eq!(sink_inputs.len(), 2);
+
+        let firefox = &sink_inputs[0];
+        assert_eq!(firefox.index, 42);
+        assert_eq!(firefox.application_name, "Firefox");
+        assert_eq!(firefox.media_name, "Song \"Live\" - YouTube");
+        // Channel order follows channel_map, not the JSON object order
+        assert_eq!(firefox.channel_map, ["front-right", "front-left"]);
+        assert_eq!(firefox.channel_volumes, [0.5, 1.0]);
+        assert!(!firefox.corked);
+
+        assert!(sink_inputs[1].corked);
+    }
+
+    #[test]
+    fn text_and_json_agree_on_balance() {
+        let text = parse_sink_inputs_text(SINK_INPUTS_TEXT);
+        let json = parse_sink_inputs_json(SINK_INPUTS_JSON).unwrap();
+        assert_eq!(text[0].balance(), json[0].balance());
+        assert_eq!(text[0].balance(), -0.5);
+    }
+
+    #[test]
+    fn parses_sinks() {
+        for sinks in [
+            parse_sinks_text(SINKS_TEXT),
+            parse_sinks_json(SINKS_JSON).unwrap(),
+        ] {
+            assert_eq!(sinks.len(), 2);
+            assert_eq!(sinks[0].name, "alsa_output.pci-0000_00_1f.3.analog-stereo");
+            assert_eq!(sinks[0].description, "Built-in Audio Analog Stereo");
+            assert_eq!(sinks[0].volume, 0.75);
+            assert!(!sinks[0].muted);
+            assert!(sinks[1].muted);
+        }
+    }
+
+    #[test]
+    fn unescapes_quoted_properties() {
+        assert_eq!(
+            parse_property(r#"media.name = "A \"quoted\" \\ title""#),
+            Some(("media.name", r#"A "quoted" \ title"#.to_string()))
+        );
+        assert_eq!(parse_property("Corked: no"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert!(parse_sink_inputs_json("Sink Input #42").is_err());
+    }
+}
@@ -1,16 +1,62 @@
-use cosmic_config::{Config, ConfigGet, ConfigSet};
+use cosmic_config::{cosmic_config_derive::CosmicConfigEntry, Config, ConfigGet, CosmicConfigEntry};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use std::path::Path;
 
-const CONFIG_VERSION: u64 = 1;
+mod settings_file;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub use settings_file::RejectedKey;
+
+pub const CONFIG_ID: &str = "com.cosmic.MusicPlayer";
+
+/// How much music ducking may lower, in percent; the slider's range and what imports accept
+pub const DUCKING_AMOUNT_RANGE: RangeInclusive<u32> = 10..=90;
+
+/// Maximum volumes (in percent) the user can choose from
+pub const MAX_VOLUME_OPTIONS: [u32; 3] = [100, 125, 150];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CosmicConfigEntry)]
+#[serde(default)]
+// The schema version settings are stored under, also `AppConfig::VERSION`. Version 1 stored
+// everything as a single "config" blob, version 2 stores one key per field.
+#[version = 2]
 pub struct AppConfig {
     pub enabled_players: HashSet<String>,
     pub auto_detect_new_players: bool,
     pub selected_player: Option<String>,
     pub show_all_players: bool,
     pub hide_inactive_players: bool,
+    pub show_visualizer: bool,
+    pub max_volume_percent: u32,
+    /// Last volume the user set, per player identity
+    pub player_volumes: HashMap<String, f64>,
+    /// Players whose volume should not be restored when they appear
+    pub volume_restore_excluded: HashSet<String>,
+    pub show_system_volume: bool,
+    pub ducking_enabled: bool,
+    /// How much music is lowered while other audio plays, in percent
+    pub ducking_amount_percent: u32,
+    pub ducking_on_recording: bool,
+    pub fade_on_play_pause: bool,
+    pub fade_duration_ms: u32,
+    pub alarms: Vec<Alarm>,
+    /// Per-player customizations, keyed by player identity
+    pub player_profiles: HashMap<String, PlayerProfile>,
+    pub panel_actions: PanelActions,
+    /// Show "artist – title" next to the panel icon
+    pub show_panel_text: bool,
+    /// Longest panel text, in characters
+    pub panel_text_max_chars: u32,
+    /// Scroll panel text that does not fit instead of cutting it off
+    pub panel_text_marquee: bool,
+    pub vertical_panel_text: VerticalPanelText,
+    /// Player polling interval while the popup is open or something plays
+    pub refresh_interval_ms: u32,
+    /// Player polling interval while the popup is closed and nothing plays
+    pub idle_refresh_interval_ms: u32,
+    /// Size limit of the downloaded album art kept on disk, in megabytes
+    pub art_cache_size_mb: u32,
 }
 
 impl Default for AppConfig {
@@ -21,10 +67,182 @@ impl Default for AppConfig {
             selected_player: None,
             show_all_players: false,
             hide_inactive_players: false,
+            show_visualizer: false,
+            max_volume_percent: 100,
+            player_volumes: HashMap::new(),
+            volume_restore_excluded: HashSet::new(),
+            show_system_volume: true,
+            ducking_enabled: false,
+            ducking_amount_percent: 50,
+            ducking_on_recording: true,
+            fade_on_play_pause: false,
+            fade_duration_ms: 1500,
+            alarms: Vec::new(),
+            player_profiles: HashMap::new(),
+            panel_actions: PanelActions::default(),
+            show_panel_text: false,
+            panel_text_max_chars: 30,
+            panel_text_marquee: false,
+            vertical_panel_text: VerticalPanelText::Hide,
+            refresh_interval_ms: 500,
+            idle_refresh_interval_ms: 3000,
+            art_cache_size_mb: 50,
         }
     }
 }
 
+/// Something the panel button can do when clicked or scrolled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PanelAction {
+    None,
+    TogglePopup,
+    PlayPause,
+    Next,
+    Previous,
+    VolumeUp,
+    VolumeDown,
+    SeekForward,
+    SeekBackward,
+    RaisePlayer,
+    NextPlayer,
+    PreviousPlayer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelGesture {
+    LeftClick,
+    MiddleClick,
+    RightClick,
+    DoubleClick,
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+}
+
+/// What each mouse gesture on the panel button does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelActions {
+    pub left_click: PanelAction,
+    pub middle_click: PanelAction,
+    pub right_click: PanelAction,
+    pub double_click: PanelAction,
+    pub scroll_up: PanelAction,
+    pub scroll_down: PanelAction,
+    pub scroll_left: PanelAction,
+    pub scroll_right: PanelAction,
+    /// Wheel lines to scroll before a scroll action triggers
+    pub scroll_threshold: f32,
+}
+
+impl Default for PanelActions {
+    fn default() -> Self {
+        Self {
+            left_click: PanelAction::TogglePopup,
+            middle_click: PanelAction::PlayPause,
+            right_click: PanelAction::None,
+            double_click: PanelAction::None,
+            scroll_up: PanelAction::Next,
+            scroll_down: PanelAction::Previous,
+            scroll_left: PanelAction::None,
+            scroll_right: PanelAction::None,
+            scroll_threshold: 1.0,
+        }
+    }
+}
+
+impl PanelActions {
+    pub fn get(&self, gesture: PanelGesture) -> PanelAction {
+        let action = self.configured(gesture);
+
+        // The popup, and with it these settings, must stay reachable
+        if gesture == PanelGesture::LeftClick && !self.opens_popup() {
+            PanelAction::TogglePopup
+        } else {
+            action
+        }
+    }
+
+    fn configured(&self, gesture: PanelGesture) -> PanelAction {
+        match gesture {
+            PanelGesture::LeftClick => self.left_click,
+            PanelGesture::MiddleClick => self.middle_click,
+            PanelGesture::RightClick => self.right_click,
+            PanelGesture::DoubleClick => self.double_click,
+            PanelGesture::ScrollUp => self.scroll_up,
+            PanelGesture::ScrollDown => self.scroll_down,
+            PanelGesture::ScrollLeft => self.scroll_left,
+            PanelGesture::ScrollRight => self.scroll_right,
+        }
+    }
+
+    fn set(&mut self, gesture: PanelGesture, action: PanelAction) {
+        let slot = match gesture {
+            PanelGesture::LeftClick => &mut self.left_click,
+            PanelGesture::MiddleClick => &mut self.middle_click,
+            PanelGesture::RightClick => &mut self.right_click,
+            PanelGesture::DoubleClick => &mut self.double_click,
+            PanelGesture::ScrollUp => &mut self.scroll_up,
+            PanelGesture::ScrollDown => &mut self.scroll_down,
+            PanelGesture::ScrollLeft => &mut self.scroll_left,
+            PanelGesture::ScrollRight => &mut self.scroll_right,
+        };
+        *slot = action;
+    }
+
+    fn opens_popup(&self) -> bool {
+        [
+            self.left_click,
+            self.middle_click,
+            self.right_click,
+            self.double_click,
+        ]
+        .contains(&PanelAction::TogglePopup)
+    }
+}
+
+/// Panel text on a vertical panel, where text cannot be rotated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerticalPanelText {
+    Hide,
+    /// One character per line
+    Stack,
+}
+
+/// How a player is shown and set up when it appears
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerProfile {
+    /// Shown instead of the player identity
+    pub display_name: Option<String>,
+    /// Icon name used on the player card and in the panel
+    pub icon_name: Option<String>,
+    /// Volume set when the player appears and no volume is remembered
+    pub default_volume: Option<f64>,
+    /// Leaves the player out of the player cards and player selection
+    pub hidden: bool,
+    /// Sink name the player's stream is moved to when it appears
+    pub preferred_output: Option<String>,
+}
+
+/// A wake-up alarm that starts playback at a given time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alarm {
+    pub enabled: bool,
+    pub hour: u32,
+    pub minute: u32,
+    /// Days the alarm repeats on (0 is Monday), empty for a one-time alarm
+    pub weekdays: Vec<u32>,
+    /// Player identity to start, the selected player when `None`
+    pub player: Option<String>,
+    /// URI or playlist to open before starting playback
+    pub uri: Option<String>,
+    /// Time over which the volume is raised from silence
+    pub ramp_minutes: u32,
+    pub volume: f64,
+}
+
 pub struct ConfigManager {
     config: Config,
     app_config: AppConfig,
@@ -32,19 +250,44 @@ pub struct ConfigManager {
 
 impl ConfigManager {
     pub fn new() -> anyhow::Result<Self> {
-        let config = Config::new("com.cosmic.MusicPlayer", CONFIG_VERSION)?;
-        let app_config = match config.get::<AppConfig>("config") {
-            Ok(config) => config,
-            Err(_) => {
-                let default_config = AppConfig::default();
-                config.set("config", &default_config)?;
-                default_config
+        let config = Config::new(CONFIG_ID, AppConfig::VERSION)?;
+        let app_config = match AppConfig::get_entry(&config) {
+            Ok(app_config) => app_config,
+            Err((errors, app_config)) => {
+                if nothing_stored(&errors) {
+                    // Nothing stored for this version yet, carry over older settings
+                    match migrate() {
+                        Some(migrated) => {
+                            migrated.write_entry(&config)?;
+                            migrated
+                        }
+                        None => app_config,
+                    }
+                } else {
+                    // Keep every key that did load instead of resetting to defaults
+                    for e in errors
+                        .iter()
+                        .filter(|e| !matches!(e, cosmic_config::Error::NotFound))
+                    {
+                        eprintln!("Failed to load setting: {}", e);
+                    }
+                    app_config
+                }
             }
         };
 
         Ok(Self { config, app_config })
     }
 
+    /// Applies settings changed outside this instance (another applet or a manual edit)
+    pub fn reload(&mut self, app_config: AppConfig) -> bool {
+        if self.app_config == app_config {
+            return false;
+        }
+        self.app_config = app_config;
+        true
+    }
+
     pub fn get_selected_player(&self) -> Option<String> {
         self.app_config.selected_player.clone()
     }
@@ -89,8 +332,376 @@ impl ConfigManager {
         self.save_config()
     }
 
+    pub fn get_show_visualizer(&self) -> bool {
+        self.app_config.show_visualizer
+    }
+
+    pub fn set_show_visualizer(&mut self, show_visualizer: bool) -> anyhow::Result<()> {
+        self.app_config.show_visualizer = show_visualizer;
+        self.save_config()
+    }
+
+    /// Upper end of the volume sliders, where 1.0 is 100%
+    pub fn get_max_volume(&self) -> f64 {
+        f64::from(self.get_max_volume_percent()) / 100.0
+    }
+
+    pub fn get_max_volume_percent(&self) -> u32 {
+        self.app_config.max_volume_percent
+    }
+
+    pub fn set_max_volume_percent(&mut self, percent: u32) -> anyhow::Result<()> {
+        self.app_config.max_volume_percent = percent.clamp(100, 150);
+        self.save_config()
+    }
+
+    /// Volume to restore for a player, unless the user opted out for it
+    pub fn get_restorable_volume(&self, player_name: &str) -> Option<f64> {
+        if self.app_config.volume_restore_excluded.contains(player_name) {
+            return None;
+        }
+        self.app_config.player_volumes.get(player_name).copied()
+    }
+
+    pub fn set_player_volume(&mut self, player_name: String, volume: f64) -> anyhow::Result<()> {
+        if self.app_config.player_volumes.get(&player_name) == Some(&volume) {
+            return Ok(());
+        }
+        self.app_config.player_volumes.insert(player_name, volume);
+        self.save_config()
+    }
+
+    pub fn get_remember_volume(&self, player_name: &str) -> bool {
+        !self.app_config.volume_restore_excluded.contains(player_name)
+    }
+
+    pub fn set_remember_volume(&mut self, player_name: String, remember: bool) -> anyhow::Result<()> {
+        if remember {
+            self.app_config.volume_restore_excluded.remove(&player_name);
+        } else {
+            self.app_config.volume_restore_excluded.insert(player_name);
+        }
+        self.save_config()
+    }
+
+    pub fn get_show_system_volume(&self) -> bool {
+        self.app_config.show_system_volume
+    }
+
+    pub fn set_show_system_volume(&mut self, show_system_volume: bool) -> anyhow::Result<()> {
+        self.app_config.show_system_volume = show_system_volume;
+        self.save_config()
+    }
+
+    pub fn get_ducking_enabled(&self) -> bool {
+        self.app_config.ducking_enabled
+    }
+
+    pub fn set_ducking_enabled(&mut self, enabled: bool) -> anyhow::Result<()> {
+        self.app_config.ducking_enabled = enabled;
+        self.save_config()
+    }
+
+    pub fn get_ducking_amount_percent(&self) -> u32 {
+        self.app_config.ducking_amount_percent
+    }
+
+    pub fn set_ducking_amount_percent(&mut self, percent: u32) -> anyhow::Result<()> {
+        self.app_config.ducking_amount_percent =
+            percent.clamp(*DUCKING_AMOUNT_RANGE.start(), *DUCKING_AMOUNT_RANGE.end());
+        self.save_config()
+    }
+
+    pub fn get_ducking_on_recording(&self) -> bool {
+        self.app_config.ducking_on_recording
+    }
+
+    pub fn set_ducking_on_recording(&mut self, enabled: bool) -> anyhow::Result<()> {
+        self.app_config.ducking_on_recording = enabled;
+        self.save_config()
+    }
+
+    pub fn get_fade_on_play_pause(&self) -> bool {
+        self.app_config.fade_on_play_pause
+    }
+
+    pub fn set_fade_on_play_pause(&mut self, enabled: bool) -> anyhow::Result<()> {
+        self.app_config.fade_on_play_pause = enabled;
+        self.save_config()
+    }
+
+    pub fn get_fade_duration_ms(&self) -> u32 {
+        self.app_config.fade_duration_ms
+    }
+
+    pub fn set_fade_duration_ms(&mut self, duration_ms: u32) -> anyhow::Result<()> {
+        self.app_config.fade_duration_ms = duration_ms;
+        self.save_config()
+    }
+
+    pub fn get_alarms(&self) -> &[Alarm] {
+        &self.app_config.alarms
+    }
+
+    pub fn add_alarm(&mut self, alarm: Alarm) -> anyhow::Result<()> {
+        self.app_config.alarms.push(alarm);
+        self.save_config()
+    }
+
+    pub fn remove_alarm(&mut self, index: usize) -> anyhow::Result<()> {
+        if index < self.app_config.alarms.len() {
+            self.app_config.alarms.remove(index);
+            self.save_config()?;
+        }
+        Ok(())
+    }
+
+    pub fn set_alarm_enabled(&mut self, index: usize, enabled: bool) -> anyhow::Result<()> {
+        if let Some(alarm) = self.app_config.alarms.get_mut(index) {
+            alarm.enabled = enabled;
+            self.save_config()?;
+        }
+        Ok(())
+    }
+
+    pub fn get_player_profile(&self, identity: &str) -> Option<&PlayerProfile> {
+        self.app_config.player_profiles.get(identity)
+    }
+
+    pub fn set_player_profile(&mut self, identity: String, profile: PlayerProfile) -> anyhow::Result<()> {
+        if profile == PlayerProfile::default() {
+            self.app_config.player_profiles.remove(&identity);
+        } else {
+            self.app_config.player_profiles.insert(identity, profile);
+        }
+        self.save_config()
+    }
+
+    /// The profile's display name, or the identity itself
+    pub fn get_display_name<'a>(&'a self, identity: &'a str) -> &'a str {
+        self.get_player_profile(identity)
+            .and_then(|profile| profile.display_name.as_deref())
+            .unwrap_or(identity)
+    }
+
+    pub fn is_player_hidden(&self, identity: &str) -> bool {
+        self.get_player_profile(identity)
+            .map(|profile| profile.hidden)
+            .unwrap_or(false)
+    }
+
+    pub fn get_panel_actions(&self) -> &PanelActions {
+        &self.app_config.panel_actions
+    }
+
+    pub fn set_panel_action(&mut self, gesture: PanelGesture, action: PanelAction) -> anyhow::Result<()> {
+        self.app_config.panel_actions.set(gesture, action);
+        self.save_config()
+    }
+
+    pub fn set_scroll_threshold(&mut self, threshold: f32) -> anyhow::Result<()> {
+        self.app_config.panel_actions.scroll_threshold = threshold.clamp(0.5, 5.0);
+        self.save_config()
+    }
+
+    pub fn get_show_panel_text(&self) -> bool {
+        self.app_config.show_panel_text
+    }
+
+    pub fn set_show_panel_text(&mut self, show: bool) -> anyhow::Result<()> {
+        self.app_config.show_panel_text = show;
+        self.save_config()
+    }
+
+    pub fn get_panel_text_max_chars(&self) -> u32 {
+        self.app_config.panel_text_max_chars
+    }
+
+    pub fn set_panel_text_max_chars(&mut self, max_chars: u32) -> anyhow::Result<()> {
+        self.app_config.panel_text_max_chars = max_chars.clamp(10, 80);
+        self.save_config()
+    }
+
+    pub fn get_panel_text_marquee(&self) -> bool {
+        self.app_config.panel_text_marquee
+    }
+
+    pub fn set_panel_text_marquee(&mut self, marquee: bool) -> anyhow::Result<()> {
+        self.app_config.panel_text_marquee = marquee;
+        self.save_config()
+    }
+
+    pub fn get_vertical_panel_text(&self) -> VerticalPanelText {
+        self.app_config.vertical_panel_text
+    }
+
+    pub fn set_vertical_panel_text(&mut self, vertical: VerticalPanelText) -> anyhow::Result<()> {
+        self.app_config.vertical_panel_text = vertical;
+        self.save_config()
+    }
+
+    pub fn get_refresh_interval_ms(&self) -> u32 {
+        self.app_config.refresh_interval_ms
+    }
+
+    pub fn set_refresh_interval_ms(&mut self, interval_ms: u32) -> anyhow::Result<()> {
+        self.app_config.refresh_interval_ms = interval_ms.clamp(250, 2000);
+        self.save_config()
+    }
+
+    pub fn get_idle_refresh_interval_ms(&self) -> u32 {
+        self.app_config.idle_refresh_interval_ms
+    }
+
+    pub fn set_idle_refresh_interval_ms(&mut self, interval_ms: u32) -> anyhow::Result<()> {
+        self.app_config.idle_refresh_interval_ms = interval_ms.clamp(1000, 10000);
+        self.save_config()
+    }
+
+    pub fn get_art_cache_size_mb(&self) -> u32 {
+        self.app_config.art_cache_size_mb
+    }
+
+    pub fn set_art_cache_size_mb(&mut self, size_mb: u32) -> anyhow::Result<()> {
+        self.app_config.art_cache_size_mb = size_mb.clamp(10, 500);
+        self.save_config()
+    }
+
+    pub fn export_settings(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, settings_file::export(&self.app_config)?)?;
+        Ok(())
+    }
+
+    /// Applies the settings in `path`, returning the keys that were left out
+    pub fn import_settings(&mut self, path: &Path) -> anyhow::Result<Vec<RejectedKey>> {
+        let contents = std::fs::read_to_string(path)?;
+        let (app_config, rejected) = settings_file::import(&self.app_config, &contents)?;
+        self.app_config = app_config;
+        self.save_config()?;
+        Ok(rejected)
+    }
+
     fn save_config(&self) -> anyhow::Result<()> {
-        self.config.set("config", &self.app_config)?;
+        self.app_config.write_entry(&self.config)?;
         Ok(())
     }
 }
+
+/// Whether no setting at all is stored under the current version yet
+///
+/// Keys of fields added since the settings were last written are missing too, those fall
+/// back to their defaults instead of re-running the migration over the stored settings.
+fn nothing_stored(errors: &[cosmic_config::Error]) -> bool {
+    let field_count = serde_json::to_value(AppConfig::default())
+        .ok()
+        .and_then(|value| value.as_object().map(|fields| fields.len()))
+        .unwrap_or(usize::MAX);
+    errors.len() >= field_count
+        && errors
+            .iter()
+            .all(|e| matches!(e, cosmic_config::Error::NotFound))
+}
+
+/// Loads settings stored by the newest older schema version, if any
+fn migrate() -> Option<AppConfig> {
+    (1..AppConfig::VERSION).rev().find_map(|version| {
+        let old_config = Config::new(CONFIG_ID, version).ok()?;
+        match version {
+            1 => migrate_v1(&old_config),
+            _ => None,
+        }
+    })
+}
+
+/// Version 1 kept the whole `AppConfig` in one key; missing fields fall back to their defaults
+fn migrate_v1(old_config: &Config) -> Option<AppConfig> {
+    old_config.get::<AppConfig>("config").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmic_config::ConfigSet;
+
+    fn not_found(count: usize) -> Vec<cosmic_config::Error> {
+        (0..count).map(|_| cosmic_config::Error::NotFound).collect()
+    }
+
+    fn field_count() -> usize {
+        serde_json::to_value(AppConfig::default())
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .len()
+    }
+
+    #[test]
+    fn empty_store_is_migrated() {
+        assert!(nothing_stored(&not_found(field_count())));
+    }
+
+    #[test]
+    fn store_missing_one_key_is_not_migrated() {
+        assert!(!nothing_stored(&not_found(field_count() - 1)));
+    }
+
+    #[test]
+    fn store_with_no_missing_keys_is_not_migrated() {
+        assert!(!nothing_stored(&[]));
+    }
+
+    /// `AppConfig` as version 1 stored it
+    #[derive(Serialize)]
+    struct AppConfigV1 {
+        enabled_players: HashSet<String>,
+        auto_detect_new_players: bool,
+        selected_player: Option<String>,
+        show_all_players: bool,
+        hide_inactive_players: bool,
+    }
+
+    #[test]
+    fn v1_settings_carry_over() {
+        let dir = std::env::temp_dir().join(format!("music-player-migrate-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let old_config = Config::with_custom_path(CONFIG_ID, 1, dir.clone()).unwrap();
+        let v1 = AppConfigV1 {
+            enabled_players: HashSet::from(["Spotify".to_string(), "VLC".to_string()]),
+            auto_detect_new_players: false,
+            selected_player: Some("Spotify".to_string()),
+            show_all_players: true,
+            hide_inactive_players: true,
+        };
+        old_config.set("config", &v1).unwrap();
+
+        let migrated = migrate_v1(&old_config).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(migrated.enabled_players, v1.enabled_players);
+        assert_eq!(migrated.auto_detect_new_players, v1.auto_detect_new_players);
+        assert_eq!(migrated.selected_player, v1.selected_player);
+        assert_eq!(migrated.show_all_players, v1.show_all_players);
+        assert_eq!(migrated.hide_inactive_players, v1.hide_inactive_players);
+        // Fields added since version 1 start out at their defaults
+        assert_eq!(
+            AppConfig {
+                enabled_players: HashSet::new(),
+                auto_detect_new_players: true,
+                selected_player: None,
+                show_all_players: false,
+                hide_inactive_players: false,
+                ..migrated
+            },
+            AppConfig::default()
+        );
+    }
+
+    #[test]
+    fn missing_v1_settings_are_not_migrated() {
+        let dir = std::env::temp_dir().join(format!("music-player-no-v1-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let old_config = Config::with_custom_path(CONFIG_ID, 1, dir.clone()).unwrap();
+        assert!(migrate_v1(&old_config).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
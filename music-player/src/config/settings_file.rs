@@ -0,0 +1,285 @@
+//! Sharing settings as a commented TOML file.
+//!
+//! Imports are applied key by key on top of the current settings, so a file with a
+//! bad or unknown key still brings in everything else.
+
+use super::{AppConfig, DUCKING_AMOUNT_RANGE};
+use anyhow::Result;
+
+/// Comment written above each setting, also the list of keys an import accepts
+const SETTING_DOCS: &[(&str, &str)] = &[
+    ("enabled_players", "Player identities shown by the applet"),
+    ("auto_detect_new_players", "Enable players automatically when they first appear"),
+    ("selected_player", "Player controlled in single-player mode"),
+    ("show_all_players", "Show a card for every player instead of a single one"),
+    ("hide_inactive_players", "Hide stopped players in multi-player mode"),
+    ("show_visualizer", "Draw a spectrum of what is playing"),
+    ("max_volume_percent", "Highest player volume, 100, 125 or 150"),
+    ("player_volumes", "Last volume per player identity, 0.0 to 1.5"),
+    ("volume_restore_excluded", "Players whose volume is not restored"),
+    ("show_system_volume", "Show the system volume and output in the popup"),
+    ("ducking_enabled", "Lower music while other audio plays"),
+    ("ducking_amount_percent", "How much music is lowered, 10 to 90"),
+    ("ducking_on_recording", "Also lower music while a microphone records"),
+    ("fade_on_play_pause", "Fade the volume around play and pause"),
+    ("fade_duration_ms", "Fade length in milliseconds, 250 to 5000"),
+    ("alarms", "Wake-up alarms, weekdays count from 0 (Monday)"),
+    (
+        "player_profiles",
+        "Per-player display_name, icon_name, default_volume, hidden and preferred_output",
+    ),
+    (
+        "panel_actions",
+        "Panel button gestures: None, TogglePopup, PlayPause, Next, Previous, VolumeUp, \
+         VolumeDown, SeekForward, SeekBackward, RaisePlayer, NextPlayer or PreviousPlayer",
+    ),
+    ("show_panel_text", "Show \"artist – title\" next to the panel icon"),
+    ("panel_text_max_chars", "Longest panel text in characters, 10 to 80"),
+    ("panel_text_marquee", "Scroll panel text that does not fit"),
+    ("vertical_panel_text", "Panel text on vertical panels: Hide or Stack"),
+    (
+        "refresh_interval_ms",
+        "Player polling while the popup is open or something plays, 250 to 2000",
+    ),
+    (
+        "idle_refresh_interval_ms",
+        "Player polling while the popup is closed and nothing plays, 1000 to 10000",
+    ),
+    ("art_cache_size_mb", "Disk space for downloaded album art in megabytes, 10 to 500"),
+];
+
+/// A key from an imported file that was not applied
+#[derive(Debug, Clone)]
+pub struct RejectedKey {
+    pub key: String,
+    pub reason: String,
+}
+
+pub fn export(app_config: &AppConfig) -> Result<String> {
+    let table = toml::Table::try_from(app_config)?;
+
+    let mut values = String::from("# COSMIC Music Player settings\n");
+    let mut tables = String::new();
+    for (key, doc) in SETTING_DOCS {
+        let Some(value) = table.get(*key) else {
+            continue;
+        };
+
+        let mut entry = toml::Table::new();
+        entry.insert(key.to_string(), value.clone());
+        let snippet = toml::to_string(&entry)?;
+
+        // Plain values must come before any [table], or they would end up inside it
+        let section = if snippet.starts_with('[') {
+            &mut tables
+        } else {
+            &mut values
+        };
+        section.push_str(&format!("\n# {}\n{}", doc, snippet));
+    }
+
+    Ok(values + &tables)
+}
+
+/// Applies the valid keys of `contents` on top of `current`
+pub fn import(current: &AppConfig, contents: &str) -> Result<(AppConfig, Vec<RejectedKey>)> {
+    let imported: toml::Table = contents.parse()?;
+    let mut merged = toml::Table::try_from(current)?;
+    let mut app_config = current.clone();
+    let mut rejected = Vec::new();
+
+    for (key, value) in imported {
+        if !SETTING_DOCS.iter().any(|(known, _)| *known == key) {
+            rejected.push(RejectedKey {
+                key,
+                reason: "unknown setting".to_string(),
+            });
+            continue;
+        }
+
+        let previous = merged.insert(key.clone(), value);
+        let checked = toml::Value::Table(merged.clone())
+            .try_into::<AppConfig>()
+            .map_err(|e| e.message().to_string())
+            .and_then(|candidate| validate(&key, &candidate).map(|_| candidate));
+
+        match checked {
+            Ok(candidate) => app_config = candidate,
+            Err(reason) => {
+                // Put the current value back so later keys are checked against it
+                match previous {
+                    Some(previous) => merged.insert(key.clone(), previous),
+                    None => merged.remove(&key),
+                };
+                rejected.push(RejectedKey { key, reason });
+            }
+        }
+    }
+
+    Ok((app_config, rejected))
+}
+
+/// Range checks the UI enforces but a hand-written file might not
+fn validate(key: &str, app_config: &AppConfig) -> Result<(), String> {
+    match key {
+        "max_volume_percent" if !(100..=150).contains(&app_config.max_volume_percent) => {
+            Err("must be between 100 and 150".to_string())
+        }
+        "ducking_amount_percent"
+            if !DUCKING_AMOUNT_RANGE.contains(&app_config.ducking_amount_percent) =>
+        {
+            Err(format!(
+                "must be between {} and {}",
+                DUCKING_AMOUNT_RANGE.start(),
+                DUCKING_AMOUNT_RANGE.end()
+            ))
+        }
+        "fade_duration_ms" if !(250..=5000).contains(&app_config.fade_duration_ms) => {
+            Err("must be between 250 and 5000".to_string())
+        }
+        "player_volumes"
+            if app_config
+                .player_volumes
+                .values()
+                .any(|volume| !(0.0..=1.5).contains(volume)) =>
+        {
+            Err("volumes must be between 0.0 and 1.5".to_string())
+        }
+        "alarms"
+            if app_config.alarms.iter().any(|alarm| {
+                alarm.hour > 23
+                    || alarm.minute > 59
+                    || alarm.weekdays.iter().any(|day| *day > 6)
+                    || !(0.0..=1.5).contains(&alarm.volume)
+            }) =>
+        {
+            Err("alarm time, weekday or volume out of range".to_string())
+        }
+        "player_profiles"
+            if app_config.player_profiles.values().any(|profile| {
+                profile
+                    .default_volume
+                    .is_some_and(|volume| !(0.0..=1.5).contains(&volume))
+            }) =>
+        {
+            Err("default volumes must be between 0.0 and 1.5".to_string())
+        }
+        "panel_text_max_chars" if !(10..=80).contains(&app_config.panel_text_max_chars) => {
+            Err("must be between 10 and 80".to_string())
+        }
+        "refresh_interval_ms" if !(250..=2000).contains(&app_config.refresh_interval_ms) => {
+            Err("must be between 250 and 2000".to_string())
+        }
+        "idle_refresh_interval_ms"
+            if !(1000..=10000).contains(&app_config.idle_refresh_interval_ms) =>
+        {
+            Err("must be between 1000 and 10000".to_string())
+        }
+        "art_cache_size_mb" if !(10..=500).contains(&app_config.art_cache_size_mb) => {
+            Err("must be between 10 and 500".to_string())
+        }
+        "panel_actions" if !(0.5..=5.0).contains(&app_config.panel_actions.scroll_threshold) => {
+            Err("scroll_threshold must be between 0.5 and 5.0".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Alarm, PanelAction, PlayerProfile};
+
+    const PARTLY_INVALID: &str = include_str!("fixtures/partly_invalid.toml");
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let mut app_config = AppConfig {
+            show_all_players: true,
+            max_volume_percent: 150,
+            ducking_enabled: true,
+            ducking_amount_percent: 30,
+            selected_player: Some("Spotify".to_string()),
+            art_cache_size_mb: 200,
+            ..AppConfig::default()
+        };
+        app_config.enabled_players.insert("Spotify".to_string());
+        app_config
+            .player_volumes
+            .insert("Spotify".to_string(), 0.75);
+        app_config.player_profiles.insert(
+            "Spotify".to_string(),
+            PlayerProfile {
+                display_name: Some("Music".to_string()),
+                default_volume: Some(0.5),
+                ..PlayerProfile::default()
+            },
+        );
+        app_config.alarms.push(Alarm {
+            enabled: true,
+            hour: 7,
+            minute: 30,
+            weekdays: vec![0, 1, 2, 3, 4],
+            player: None,
+            uri: Some("spotify:playlist:morning".to_string()),
+            ramp_minutes: 5,
+            volume: 0.6,
+        });
+        app_config.panel_actions.double_click = PanelAction::Next;
+
+        let exported = export(&app_config).unwrap();
+        let (imported, rejected) = import(&AppConfig::default(), &exported).unwrap();
+
+        assert!(rejected.is_empty(), "rejected {:?}", rejected);
+        assert_eq!(imported, app_config);
+    }
+
+    #[test]
+    fn import_rejects_bad_keys_and_applies_the_rest() {
+        let current = AppConfig::default();
+        let (imported, rejected) = import(&current, PARTLY_INVALID).unwrap();
+
+        let mut rejected_keys: Vec<&str> = rejected.iter().map(|r| r.key.as_str()).collect();
+        rejected_keys.sort();
+        assert_eq!(
+            rejected_keys,
+            ["ducking_amount_percent", "favourite_colour"]
+        );
+
+        assert_eq!(
+            imported.ducking_amount_percent,
+            current.ducking_amount_percent
+        );
+        assert!(imported.show_visualizer);
+        assert_eq!(imported.fade_duration_ms, 800);
+        assert_eq!(imported.player_volumes.get("Spotify"), Some(&0.4));
+    }
+
+    #[test]
+    fn ducking_amounts_the_slider_allows_are_imported() {
+        for percent in [*DUCKING_AMOUNT_RANGE.start(), *DUCKING_AMOUNT_RANGE.end()] {
+            let app_config = AppConfig {
+                ducking_amount_percent: percent,
+                ..AppConfig::default()
+            };
+            let exported = export(&app_config).unwrap();
+            let (imported, rejected) = import(&AppConfig::default(), &exported).unwrap();
+            assert!(rejected.is_empty(), "rejected {:?}", rejected);
+            assert_eq!(imported.ducking_amount_percent, percent);
+        }
+    }
+
+    #[test]
+    fn import_rejects_a_value_of_the_wrong_type() {
+        let (imported, rejected) =
+            import(&AppConfig::default(), "fade_duration_ms = \"slow\"").unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].key, "fade_duration_ms");
+        assert_eq!(imported, AppConfig::default());
+    }
+
+    #[test]
+    fn import_fails_on_invalid_toml() {
+        assert!(import(&AppConfig::default(), "show_visualizer = ").is_err());
+    }
+}
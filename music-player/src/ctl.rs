@@ -0,0 +1,227 @@
+//! `music-player ctl ...`: controls the player the applet targets from the command line,
+//! e.g. for custom keyboard shortcuts.
+
+use crate::config::ConfigManager;
+use crate::music::MusicController;
+use anyhow::{anyhow, bail, Result};
+use mpris::PlaybackStatus;
+use serde::Serialize;
+
+const USAGE: &str = "Usage: music-player ctl <command>
+
+Commands:
+  play-pause           Toggle playback
+  next                 Skip to the next track
+  previous             Go back to the previous track
+  volume <+N%|-N%|N%>  Change or set the volume
+  status [--json]      Show what is playing
+  select <player>      Control <player> from now on";
+
+#[derive(Serialize)]
+struct Status {
+    identity: String,
+    title: String,
+    artist: String,
+    status: String,
+    volume: f64,
+}
+
+/// Runs a `ctl` subcommand and returns the process exit code
+pub fn run(args: &[String]) -> i32 {
+    match run_command(args) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+fn run_command(args: &[String]) -> Result<()> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let mut config = ConfigManager::new().ok();
+    let mut music_controller = MusicController::new();
+
+    let player = player_to_control(&mut music_controller, config.as_ref())?;
+    music_controller.find_selected_player(player.as_deref())?;
+
+    // Fail instead of silently doing nothing, so scripts can tell
+    let needs_player = matches!(
+        args.as_slice(),
+        ["play-pause"] | ["next"] | ["previous"] | ["volume", _] | ["status", ..]
+    );
+    if needs_player && music_controller.get_player_info().identity.is_empty() {
+        bail!("No player to control");
+    }
+
+    match args.as_slice() {
+        ["play-pause"] => music_controller.play_pause(),
+        ["next"] => music_controller.next(),
+        ["previous"] => music_controller.previous(),
+        ["volume", change] => {
+            let info = music_controller.get_player_info();
+            let max_volume = config.as_ref().map(|c| c.get_max_volume()).unwrap_or(1.0);
+            let volume = parse_volume(change, info.volume)?.clamp(0.0, max_volume);
+
+            music_controller.set_volume(volume)?;
+            if let Some(ref mut config) = config {
+                let _ = config.set_player_volume(info.identity, volume);
+            }
+            Ok(())
+        }
+        ["status", rest @ ..] => {
+            let info = music_controller.get_player_info();
+            let status = Status {
+                identity: info.identity,
+                title: info.title,
+                artist: info.artist,
+                status: format!("{:?}", info.status),
+                volume: info.volume,
+            };
+
+            if rest == ["--json"] {
+                println!("{}", serde_json::to_string(&status)?);
+            } else if rest.is_empty() {
+                println!("Player: {}", status.identity);
+                println!("Status: {}", status.status);
+                println!("Title:  {}", status.title);
+                println!("Artist: {}", status.artist);
+                println!("Volume: {:.0}%", status.volume * 100.0);
+            } else {
+                bail!("{}", USAGE);
+            }
+            Ok(())
+        }
+        ["select", player] => {
+            music_controller.discover_all_players()?;
+            let discovered = music_controller.get_discovered_players();
+            if !discovered.iter().any(|p| p.identity == *player) {
+                let names: Vec<&str> = discovered.iter().map(|p| p.identity.as_str()).collect();
+                bail!("Unknown player \"{}\", running players: {}", player, names.join(", "));
+            }
+
+            let config = config
+                .as_mut()
+                .ok_or_else(|| anyhow!("Settings are not available"))?;
+            config.set_selected_player(Some(player.to_string()))
+        }
+        _ => bail!("{}", USAGE),
+    }
+}
+
+/// Identity of the player the applet's panel button stands for: the selected one, or in
+/// multi-player mode the first visible one that is playing. `None` lets the controller
+/// pick whichever player is active.
+///
+/// Unlike the applet, which keeps acting on a player paused in multi-player mode, a
+/// command run while nothing plays falls back to the active player.
+fn player_to_control(
+    music_controller: &mut MusicController,
+    config: Option<&ConfigManager>,
+) -> Result<Option<String>> {
+    let Some(config) = config else {
+        return Ok(None);
+    };
+    if !config.get_show_all_players() {
+        return Ok(config.get_selected_player());
+    }
+
+    music_controller.discover_all_players()?;
+    Ok(music_controller
+        .get_all_players_info()
+        .into_iter()
+        .find(|player| {
+            player.status == PlaybackStatus::Playing && !config.is_player_hidden(&player.identity)
+        })
+        .map(|player| player.identity))
+}
+
+/// Parses "+5%", "-5%" (relative) or "50%" (absolute) into a volume
+fn parse_volume(change: &str, current: f64) -> Result<f64> {
+    let invalid = || anyhow!("Invalid volume \"{}\", use +N%, -N% or N%", change);
+    let percent = change.strip_suffix('%').unwrap_or(change);
+    let (sign, number) = if let Some(number) = percent.strip_prefix('+') {
+        (1.0, number)
+    } else if let Some(number) = percent.strip_prefix('-') {
+        (-1.0, number)
+    } else {
+        (0.0, percent)
+    };
+
+    // Rules out a second sign as well as "inf" and "NaN"
+    if !number.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let value: f64 = number
+        .parse()
+        .ok()
+        .filter(|value: &f64| value.is_finite())
+        .ok_or_else(invalid)?;
+
+    if sign == 0.0 {
+        Ok(value / 100.0)
+    } else {
+        Ok(current + sign * value / 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_volume(change: &str, current: f64, expected: f64) {
+        let volume = parse_volume(change, current).unwrap();
+        assert!(
+            (volume - expected).abs() < 1e-9,
+            "{} gave {}",
+            change,
+            volume
+        );
+    }
+
+    #[test]
+    fn relative_changes() {
+        assert_volume("+5%", 0.5, 0.55);
+        assert_volume("-10%", 0.5, 0.4);
+        assert_volume("+0%", 0.3, 0.3);
+    }
+
+    #[test]
+    fn absolute_volumes() {
+        assert_volume("50%", 0.9, 0.5);
+        assert_volume("0%", 0.9, 0.0);
+        assert_volume("125%", 0.1, 1.25);
+    }
+
+    #[test]
+    fn percent_sign_is_optional() {
+        assert_volume("40", 0.9, 0.4);
+        assert_volume("+5", 0.5, 0.55);
+        assert_volume("2.5%", 0.0, 0.025);
+    }
+
+    #[test]
+    fn clamping_is_left_to_the_caller() {
+        assert_volume("-80%", 0.5, -0.3);
+        assert_volume("+80%", 0.5, 1.3);
+    }
+
+    #[test]
+    fn invalid_volumes() {
+        for change in [
+            "", "%", "+", "loud", "5%%", "+-5%", "++5%", "inf%", "NaN", "1e999%",
+        ] {
+            assert!(
+                parse_volume(change, 0.5).is_err(),
+                "{} was accepted",
+                change
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_volume_names_the_input() {
+        let message = parse_volume("loud", 0.5).unwrap_err().to_string();
+        assert!(message.contains("\"loud\""), "{}", message);
+    }
+}
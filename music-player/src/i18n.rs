@@ -0,0 +1,46 @@
+//! Fluent localization, loaded from `i18n/<language>/music_player.ftl` according to the
+//! desktop's locale.
+
+use i18n_embed::{
+    fluent::{fluent_language_loader, FluentLanguageLoader},
+    unic_langid::{CharacterDirection, LanguageIdentifier},
+    DefaultLocalizer, LanguageLoader, Localizer,
+};
+use rust_embed::RustEmbed;
+use std::sync::LazyLock;
+
+#[derive(RustEmbed)]
+#[folder = "i18n/"]
+struct Localizations;
+
+pub static LANGUAGE_LOADER: LazyLock<FluentLanguageLoader> = LazyLock::new(|| {
+    let loader: FluentLanguageLoader = fluent_language_loader!();
+    loader
+        .load_fallback_language(&Localizations)
+        .expect("Error while loading fallback language");
+    loader
+});
+
+/// Selects the best available translation for the requested languages
+pub fn init(requested_languages: &[LanguageIdentifier]) {
+    let localizer = DefaultLocalizer::new(&*LANGUAGE_LOADER, &Localizations);
+    if let Err(e) = localizer.select(requested_languages) {
+        eprintln!("Failed to load translations: {}", e);
+    }
+}
+
+/// Whether the selected language is written right to left, so rows should be mirrored
+pub fn is_rtl() -> bool {
+    LANGUAGE_LOADER.current_language().character_direction() == CharacterDirection::RTL
+}
+
+/// Looks up a translated message, e.g. `fl!("panel-text-width", chars = 30)`
+#[macro_export]
+macro_rules! fl {
+    ($message_id:literal) => {{
+        i18n_embed_fl::fl!($crate::i18n::LANGUAGE_LOADER, $message_id)
+    }};
+    ($message_id:literal, $($args:expr),*) => {{
+        i18n_embed_fl::fl!($crate::i18n::LANGUAGE_LOADER, $message_id, $($args), *)
+    }};
+}
@@ -3,12 +3,23 @@
 /// The `app` module is used by convention to indicate the main component of our
 /// application.
 mod app;
+mod art;
 mod audio;
 mod config;
+mod ctl;
+mod i18n;
 mod music;
+mod visualizer;
 
 use app::CosmicAppletMusic;
 
 fn main() -> cosmic::iced::Result {
+    i18n::init(&i18n_embed::DesktopLanguageRequester::requested_languages());
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("ctl") {
+        std::process::exit(ctl::run(&args[1..]));
+    }
+
     cosmic::applet::run::<CosmicAppletMusic>(())
 }
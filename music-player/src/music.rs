@@ -1,14 +1,18 @@
 use anyhow::Result;
 use mpris::{PlaybackStatus, Player, PlayerFinder};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use crate::audio::AudioController;
 
+/// Player name part of the applet's own MPRIS proxy, which is never a player to control
+pub const PROXY_PLAYER_NAME: &str = "cosmic_music_applet";
+
 #[derive(Debug, Clone)]
 pub struct PlayerInfo {
+    /// Empty when the player reports none, the view shows a placeholder instead
     pub title: String,
     pub artist: String,
     pub status: PlaybackStatus,
@@ -18,6 +22,10 @@ pub struct PlayerInfo {
     pub identity: String,
     pub last_changed: u64,
     pub can_control_volume: bool,
+    /// Stereo balance of the matched audio stream, if one was found
+    pub balance: Option<f64>,
+    /// Index of the matched audio stream (sink input), if one was found
+    pub stream_index: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -26,10 +34,18 @@ pub struct DiscoveredPlayer {
     pub is_active: bool,
 }
 
+/// Where a player is in its current track
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackProgress {
+    /// Track id, or the title when the player has none
+    pub track: String,
+    pub remaining: Duration,
+}
+
 impl Default for PlayerInfo {
     fn default() -> Self {
         Self {
-            title: "No music playing".to_string(),
+            title: String::new(),
             artist: String::new(),
             status: PlaybackStatus::Stopped,
             volume: 0.5,
@@ -41,6 +57,8 @@ impl Default for PlayerInfo {
                 .unwrap_or_default()
                 .as_secs(),
             can_control_volume: true,
+            balance: None,
+            stream_index: None,
         }
     }
 }
@@ -50,6 +68,8 @@ pub struct MusicController {
     player: Rc<RefCell<Option<Player>>>,
     discovered_players: Rc<RefCell<HashMap<String, DiscoveredPlayer>>>,
     all_players: Rc<RefCell<HashMap<String, Player>>>,
+    /// Stream index per bus name seen in the last discovery, `None` before the first one
+    known_players: Rc<RefCell<Option<HashMap<String, Option<u32>>>>>,
     audio_controller: Option<Arc<AudioController>>,
 }
 
@@ -71,10 +91,15 @@ impl MusicController {
             player: Rc::new(RefCell::new(None)),
             discovered_players: Rc::new(RefCell::new(HashMap::new())),
             all_players: Rc::new(RefCell::new(HashMap::new())),
+            known_players: Rc::new(RefCell::new(None)),
             audio_controller,
         }
     }
 
+    pub fn audio_controller(&self) -> Option<Arc<AudioController>> {
+        self.audio_controller.clone()
+    }
+
     fn get_current_timestamp() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -82,7 +107,9 @@ impl MusicController {
             .as_secs()
     }
 
-    pub fn discover_all_players(&mut self) -> Result<()> {
+    /// Rediscovers all players and returns the bus names of players that appeared
+    /// (or whose audio stream appeared) since the previous discovery
+    pub fn discover_all_players(&mut self) -> Result<Vec<String>> {
         let player_finder = PlayerFinder::new()?;
 
         let mut discovered_borrow = self.discovered_players.borrow_mut();
@@ -91,7 +118,7 @@ impl MusicController {
         all_players_borrow.clear();
 
         // Try to get all players
-        if let Ok(players) = player_finder.find_all() {
+        if let Ok(players) = find_players(&player_finder) {
             for player in players {
                 let identity = player.identity();
                 let bus_name = player.bus_name_player_name_part();
@@ -112,25 +139,105 @@ impl MusicController {
             }
         }
 
-        Ok(())
+        // Match audio streams to detect players whose stream started after the player itself
+        if let Some(ref audio_ctrl) = self.audio_controller {
+            let _ = audio_ctrl.refresh_sink_inputs();
+        }
+        let current: HashMap<String, Option<u32>> = all_players_borrow
+            .iter()
+            .map(|(bus_name, player)| {
+                let stream_index = self.audio_controller.as_ref().and_then(|audio_ctrl| {
+                    audio_ctrl
+                        .find_sink_input_by_name(player.identity())
+                        .map(|sink_input| sink_input.index)
+                });
+                (bus_name.clone(), stream_index)
+            })
+            .collect();
+
+        // Players that were already running on the first discovery did not "appear"
+        let appeared = match self.known_players.borrow().as_ref() {
+            Some(known) => current
+                .iter()
+                .filter(|(bus_name, stream_index)| match known.get(*bus_name) {
+                    None => true,
+                    Some(known_index) => stream_index.is_some() && known_index != *stream_index,
+                })
+                .map(|(bus_name, _)| bus_name.clone())
+                .collect(),
+            None => Vec::new(),
+        };
+        *self.known_players.borrow_mut() = Some(current);
+
+        Ok(appeared)
+    }
+
+    /// Lowers the streams of all known players while other audio plays; passing an
+    /// `amount` of 0.0 restores them
+    pub fn update_ducking(&self, amount: f64, duck_on_recording: bool) -> Result<()> {
+        let Some(ref audio_ctrl) = self.audio_controller else {
+            return Ok(());
+        };
+
+        let music_streams: HashSet<u32> = self
+            .all_players
+            .borrow()
+            .values()
+            .filter_map(|player| audio_ctrl.find_sink_input_by_name(player.identity()))
+            .map(|sink_input| sink_input.index)
+            .collect();
+
+        audio_ctrl.update_ducking(&music_streams, amount, duck_on_recording)
+    }
+
+    pub fn restore_ducking(&self) {
+        if let Some(ref audio_ctrl) = self.audio_controller {
+            audio_ctrl.restore_ducking();
+        }
+    }
+
+    pub fn get_player_identity(&self, bus_name: &str) -> Option<String> {
+        self.all_players
+            .borrow()
+            .get(bus_name)
+            .map(|player| player.identity().to_string())
     }
 
     pub fn find_active_player(&mut self) -> Result<()> {
         let player_finder = PlayerFinder::new()?;
 
-        // Try to find the first available player
-        if let Ok(player) = player_finder.find_active() {
-            *self.player.borrow_mut() = Some(player);
+        // Prefer a playing player, then a paused one, then any
+        if let Ok(players) = find_players(&player_finder) {
+            let status = |player: &Player| player.get_playback_status().ok();
+            let active = players
+                .iter()
+                .position(|player| status(player) == Some(PlaybackStatus::Playing))
+                .or_else(|| {
+                    players
+                        .iter()
+                        .position(|player| status(player) == Some(PlaybackStatus::Paused))
+                });
+            if let Some(player) = players.into_iter().nth(active.unwrap_or(0)) {
+                *self.player.borrow_mut() = Some(player);
+            }
         }
 
         Ok(())
     }
 
+    /// Targets the selected player, or any active player when none is selected
+    pub fn find_selected_player(&mut self, selected_player: Option<&str>) -> Result<()> {
+        match selected_player {
+            Some(player_name) => self.find_specific_player(player_name),
+            None => self.find_active_player(),
+        }
+    }
+
     pub fn find_specific_player(&mut self, player_name: &str) -> Result<()> {
         let player_finder = PlayerFinder::new()?;
 
         // Try to find all players and pick the one that matches the name
-        if let Ok(players) = player_finder.find_all() {
+        if let Ok(players) = find_players(&player_finder) {
             for player in players {
                 let identity = player.identity();
                 if identity == player_name {
@@ -166,22 +273,26 @@ impl MusicController {
         let title = metadata
             .title()
             .map(|s| s.to_string())
-            .unwrap_or_else(|| "Unknown".to_string());
+            .unwrap_or_default();
 
         let artist = metadata
             .artists()
             .map(|artists| artists.join(", "))
-            .unwrap_or_else(|| "Unknown Artist".to_string());
+            .unwrap_or_default();
 
         let art_url = metadata.art_url().map(|url| url.to_string());
         let bus_name = player.bus_name_player_name_part().to_string();
         let identity = player.identity().to_string();
 
         // For browsers, get actual volume from PulseAudio
+        let mut balance = None;
+        let mut stream_index = None;
         if let Some(ref audio_ctrl) = self.audio_controller {
             let _ = audio_ctrl.refresh_sink_inputs();
             if let Some(sink_input) = audio_ctrl.find_sink_input_by_name(&identity) {
                 volume = sink_input.volume;
+                balance = Some(sink_input.balance());
+                stream_index = Some(sink_input.index);
             }
         }
 
@@ -199,6 +310,8 @@ impl MusicController {
             identity,
             last_changed: Self::get_current_timestamp(),
             can_control_volume,
+            balance,
+            stream_index,
         }
     }
 
@@ -222,20 +335,24 @@ impl MusicController {
             let title = metadata
                 .title()
                 .map(|s| s.to_string())
-                .unwrap_or_else(|| "Unknown".to_string());
+                .unwrap_or_default();
 
             let artist = metadata
                 .artists()
                 .map(|artists| artists.join(", "))
-                .unwrap_or_else(|| "Unknown Artist".to_string());
+                .unwrap_or_default();
 
             let art_url = metadata.art_url().map(|url| url.to_string());
             let identity = player.identity().to_string();
 
             // For browsers, get actual volume from PulseAudio
+            let mut balance = None;
+            let mut stream_index = None;
             if let Some(ref audio_ctrl) = self.audio_controller {
                 if let Some(sink_input) = audio_ctrl.find_sink_input_by_name(&identity) {
                     volume = sink_input.volume;
+                    balance = Some(sink_input.balance());
+                    stream_index = Some(sink_input.index);
                 }
             }
 
@@ -253,6 +370,8 @@ impl MusicController {
                 identity: identity.clone(),
                 last_changed: Self::get_current_timestamp(),
                 can_control_volume,
+                balance,
+                stream_index,
             };
 
             // Separate Firefox players for deduplication
@@ -318,22 +437,36 @@ impl MusicController {
         let all_players_borrow = self.all_players.borrow();
 
         if let Some(player) = all_players_borrow.get(bus_name) {
-            // Try MPRIS first
-            if let Ok(_) = player.set_volume(volume) {
-                return Ok(());
-            }
+            self.apply_volume(player, volume)?;
+        }
 
-            // If MPRIS fails, try audio controller (for browsers)
+        Ok(())
+    }
+
+    pub fn move_player_to_output(&self, bus_name: &str, sink_name: &str) -> Result<()> {
+        let all_players_borrow = self.all_players.borrow();
+
+        if let Some(player) = all_players_borrow.get(bus_name) {
             if let Some(ref audio_ctrl) = self.audio_controller {
-                let identity = player.identity();
+                if let Some(sink_input) = audio_ctrl.find_sink_input_by_name(player.identity()) {
+                    audio_ctrl.move_sink_input(sink_input.index, sink_name)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_balance_player(&self, bus_name: &str, balance: f64) -> Result<()> {
+        let all_players_borrow = self.all_players.borrow();
 
-                // First refresh to get current sink inputs
+        if let Some(player) = all_players_borrow.get(bus_name) {
+            // Balance is only available through the audio stream
+            if let Some(ref audio_ctrl) = self.audio_controller {
                 let _ = audio_ctrl.refresh_sink_inputs();
 
-                // Try to find matching audio stream
-                if let Some(sink_input) = audio_ctrl.find_sink_input_by_name(identity) {
-                    audio_ctrl.set_sink_input_volume(sink_input.index, volume)?;
-                    return Ok(());
+                if let Some(sink_input) = audio_ctrl.find_sink_input_by_name(player.identity()) {
+                    audio_ctrl.set_sink_input_balance(sink_input.index, balance)?;
                 }
             }
         }
@@ -341,6 +474,58 @@ impl MusicController {
         Ok(())
     }
 
+    /// Time left in the current track of a player, if it reports length and position
+    pub fn get_track_progress_player(&self, bus_name: &str) -> Option<TrackProgress> {
+        let all_players_borrow = self.all_players.borrow();
+        all_players_borrow.get(bus_name).and_then(Self::track_progress)
+    }
+
+    /// Current track of the selected player and the time left in it
+    pub fn get_track_progress(&self) -> Option<TrackProgress> {
+        let player_borrow = self.player.borrow();
+        player_borrow.as_ref().and_then(Self::track_progress)
+    }
+
+    fn track_progress(player: &Player) -> Option<TrackProgress> {
+        let metadata = player.get_metadata().ok()?;
+        let length = metadata.length()?;
+        let position = player.get_position().ok()?;
+        // Not every player sets a track id, the title tells tracks apart well enough then
+        let track = metadata
+            .track_id()
+            .map(String::from)
+            .or_else(|| metadata.title().map(str::to_string))
+            .unwrap_or_default();
+        Some(TrackProgress {
+            track,
+            remaining: length.saturating_sub(position),
+        })
+    }
+
+    pub fn find_bus_name(&self, identity: &str) -> Option<String> {
+        self.all_players
+            .borrow()
+            .iter()
+            .find(|(_, player)| player.identity() == identity)
+            .map(|(bus_name, _)| bus_name.clone())
+    }
+
+    pub fn play_player(&self, bus_name: &str) -> Result<()> {
+        let all_players_borrow = self.all_players.borrow();
+        if let Some(player) = all_players_borrow.get(bus_name) {
+            player.play()?;
+        }
+        Ok(())
+    }
+
+    pub fn pause_player(&self, bus_name: &str) -> Result<()> {
+        let all_players_borrow = self.all_players.borrow();
+        if let Some(player) = all_players_borrow.get(bus_name) {
+            player.pause()?;
+        }
+        Ok(())
+    }
+
     pub fn play_pause(&self) -> Result<()> {
         let player_borrow = self.player.borrow();
 
@@ -350,6 +535,24 @@ impl MusicController {
         Ok(())
     }
 
+    pub fn play(&self) -> Result<()> {
+        let player_borrow = self.player.borrow();
+
+        if let Some(ref player) = *player_borrow {
+            player.play()?;
+        }
+        Ok(())
+    }
+
+    pub fn pause(&self) -> Result<()> {
+        let player_borrow = self.player.borrow();
+
+        if let Some(ref player) = *player_borrow {
+            player.pause()?;
+        }
+        Ok(())
+    }
+
     pub fn next(&self) -> Result<()> {
         let player_borrow = self.player.borrow();
 
@@ -368,30 +571,92 @@ impl MusicController {
         Ok(())
     }
 
+    /// Seeks the selected player by `offset` microseconds (negative to go back)
+    pub fn seek(&self, offset: i64) -> Result<()> {
+        let player_borrow = self.player.borrow();
+
+        if let Some(ref player) = *player_borrow {
+            player.seek(offset)?;
+        }
+        Ok(())
+    }
+
+    /// Brings the selected player's window to the front
+    pub fn raise(&self) -> Result<()> {
+        let player_borrow = self.player.borrow();
+
+        if let Some(ref player) = *player_borrow {
+            player.raise()?;
+        }
+        Ok(())
+    }
+
     pub fn set_volume(&self, volume: f64) -> Result<()> {
         let player_borrow = self.player.borrow();
 
         if let Some(ref player) = *player_borrow {
-            // Try MPRIS first
-            if let Ok(_) = player.set_volume(volume) {
-                return Ok(());
-            }
+            self.apply_volume(player, volume)?;
+        }
 
-            // If MPRIS fails, try audio controller (for browsers)
-            if let Some(ref audio_ctrl) = self.audio_controller {
-                let identity = player.identity();
+        Ok(())
+    }
 
-                // First refresh to get current sink inputs
-                let _ = audio_ctrl.refresh_sink_inputs();
+    /// Sets the volume through MPRIS where possible, and through the audio stream
+    /// for players without MPRIS volume or when boosting above 100%
+    fn apply_volume(&self, player: &Player, volume: f64) -> Result<()> {
+        let boost = volume > 1.0;
 
-                // Try to find matching audio stream
-                if let Some(sink_input) = audio_ctrl.find_sink_input_by_name(identity) {
-                    audio_ctrl.set_sink_input_volume(sink_input.index, volume)?;
-                    return Ok(());
+        // Try MPRIS first (MPRIS volume is capped at 1.0 by most players)
+        let mpris_ok = player.set_volume(volume.min(1.0)).is_ok();
+
+        let Some(ref audio_ctrl) = self.audio_controller else {
+            return Ok(());
+        };
+        let identity = player.identity();
+
+        if mpris_ok && !boost {
+            // Undo a previous boost on the stream, using the cached state to avoid a pactl call
+            if let Some(sink_input) = audio_ctrl.find_sink_input_by_name(identity) {
+                if sink_input.volume > 1.0 {
+                    audio_ctrl.set_sink_input_volume(sink_input.index, 1.0)?;
                 }
             }
+            return Ok(());
+        }
+
+        // If MPRIS fails or we boost, use the audio controller
+        // First refresh to get current sink inputs
+        let _ = audio_ctrl.refresh_sink_inputs();
+
+        // Try to find matching audio stream
+        if let Some(sink_input) = audio_ctrl.find_sink_input_by_name(identity) {
+            audio_ctrl.set_sink_input_volume(sink_input.index, volume)?;
         }
 
         Ok(())
     }
 }
+
+/// All MPRIS players except the applet's own proxy
+fn find_players(player_finder: &PlayerFinder) -> Result<Vec<Player>> {
+    Ok(player_finder
+        .find_all()?
+        .into_iter()
+        .filter(|player| player.bus_name_player_name_part() != PROXY_PLAYER_NAME)
+        .collect())
+}
+
+/// Asks a player to open a URI (e.g. a playlist); not covered by the mpris crate
+pub async fn open_uri(bus_name: String, uri: String) -> zbus::Result<()> {
+    let connection = zbus::Connection::session().await?;
+    connection
+        .call_method(
+            Some(format!("org.mpris.MediaPlayer2.{}", bus_name).as_str()),
+            "/org/mpris/MediaPlayer2",
+            Some("org.mpris.MediaPlayer2.Player"),
+            "OpenUri",
+            &(uri,),
+        )
+        .await?;
+    Ok(())
+}
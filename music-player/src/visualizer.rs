@@ -0,0 +1,227 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Number of bars rendered in the spectrum
+pub const SPECTRUM_BARS: usize = 24;
+
+const SAMPLE_RATE: u32 = 22050;
+const FFT_SIZE: usize = 1024;
+const MIN_FREQUENCY: f32 = 60.0;
+const MAX_FREQUENCY: f32 = 10000.0;
+/// Anything quieter than this is drawn as an empty bar
+const FLOOR_DB: f32 = -60.0;
+/// How much of the previous frame is kept so bars fall smoothly
+const DECAY: f32 = 0.8;
+
+#[derive(Debug, Clone, Default)]
+pub struct AudioLevels {
+    /// Normalized (0.0-1.0) bar heights, low to high frequencies
+    pub spectrum: Vec<f32>,
+    /// Normalized (0.0-1.0) overall level
+    pub level: f32,
+}
+
+/// Records the monitor of a single sink input and analyzes it on a background thread
+struct StreamCapture {
+    stream_index: u32,
+    child: Child,
+    levels: Arc<Mutex<AudioLevels>>,
+}
+
+impl StreamCapture {
+    fn start(stream_index: u32) -> Result<Self> {
+        // Use parec to record only what this stream plays
+        let mut child = Command::new("parec")
+            .arg(format!("--monitor-stream={}", stream_index))
+            .arg("--format=s16le")
+            .arg("--channels=1")
+            .arg(format!("--rate={}", SAMPLE_RATE))
+            .arg("--latency-msec=20")
+            .arg("--raw")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("parec has no stdout"))?;
+        let levels = Arc::new(Mutex::new(AudioLevels::default()));
+        let thread_levels = levels.clone();
+
+        // The thread ends on its own once the child is killed and stdout closes
+        thread::spawn(move || {
+            let mut buffer = vec![0u8; FFT_SIZE * 2];
+            while stdout.read_exact(&mut buffer).is_ok() {
+                let samples: Vec<f32> = buffer
+                    .chunks_exact(2)
+                    .map(|b| f32::from(i16::from_le_bytes([b[0], b[1]])) / f32::from(i16::MAX))
+                    .collect();
+
+                let spectrum = analyze_spectrum(&samples);
+                let level = analyze_level(&samples);
+
+                let Ok(mut levels) = thread_levels.lock() else {
+                    break;
+                };
+                if levels.spectrum.len() != spectrum.len() {
+                    levels.spectrum = vec![0.0; spectrum.len()];
+                }
+                for (old, new) in levels.spectrum.iter_mut().zip(spectrum) {
+                    *old = new.max(*old * DECAY);
+                }
+                levels.level = level.max(levels.level * DECAY);
+            }
+        });
+
+        Ok(Self {
+            stream_index,
+            child,
+            levels,
+        })
+    }
+
+    fn levels(&self) -> AudioLevels {
+        self.levels
+            .lock()
+            .map(|levels| levels.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for StreamCapture {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Keeps one capture per visualized player, keyed by bus name
+#[derive(Default)]
+pub struct Visualizer {
+    captures: HashMap<String, StreamCapture>,
+}
+
+impl Visualizer {
+    /// Starts captures for new streams and stops the ones that are no longer wanted
+    pub fn sync_streams(&mut self, streams: &HashMap<String, u32>) {
+        self.captures.retain(|bus_name, capture| {
+            streams.get(bus_name) == Some(&capture.stream_index)
+        });
+
+        for (bus_name, stream_index) in streams {
+            if self.captures.contains_key(bus_name) {
+                continue;
+            }
+            match StreamCapture::start(*stream_index) {
+                Ok(capture) => {
+                    self.captures.insert(bus_name.clone(), capture);
+                }
+                Err(e) => eprintln!("Failed to start visualizer capture: {}", e),
+            }
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.captures.clear();
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        !self.captures.is_empty()
+    }
+
+    pub fn levels(&self) -> HashMap<String, AudioLevels> {
+        self.captures
+            .iter()
+            .map(|(bus_name, capture)| (bus_name.clone(), capture.levels()))
+            .collect()
+    }
+}
+
+fn analyze_level(samples: &[f32]) -> f32 {
+    let mean_square = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+    normalize_db(10.0 * mean_square.max(f32::MIN_POSITIVE).log10())
+}
+
+fn analyze_spectrum(samples: &[f32]) -> Vec<f32> {
+    // Apply a Hann window to reduce spectral leakage
+    let mut real: Vec<f32> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let window = 0.5 - 0.5 * (2.0 * PI * i as f32 / (FFT_SIZE - 1) as f32).cos();
+            s * window
+        })
+        .collect();
+    let mut imag = vec![0.0; FFT_SIZE];
+    fft(&mut real, &mut imag);
+
+    let bin_width = SAMPLE_RATE as f32 / FFT_SIZE as f32;
+    let magnitudes: Vec<f32> = real
+        .iter()
+        .zip(&imag)
+        .take(FFT_SIZE / 2)
+        .map(|(re, im)| (re * re + im * im).sqrt() / (FFT_SIZE as f32 / 4.0))
+        .collect();
+
+    // Group bins into logarithmically spaced bars
+    let ratio = (MAX_FREQUENCY / MIN_FREQUENCY).powf(1.0 / SPECTRUM_BARS as f32);
+    (0..SPECTRUM_BARS)
+        .map(|bar| {
+            let low = MIN_FREQUENCY * ratio.powi(bar as i32);
+            let high = low * ratio;
+            let first = ((low / bin_width) as usize).min(magnitudes.len() - 1);
+            let last = ((high / bin_width) as usize).clamp(first + 1, magnitudes.len());
+            let peak = magnitudes[first..last].iter().copied().fold(0.0, f32::max);
+            normalize_db(20.0 * peak.max(f32::MIN_POSITIVE).log10())
+        })
+        .collect()
+}
+
+fn normalize_db(db: f32) -> f32 {
+    ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0)
+}
+
+/// In-place iterative radix-2 FFT, `real.len()` must be a power of two
+fn fft(real: &mut [f32], imag: &mut [f32]) {
+    let n = real.len();
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imag.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = real[b] * cos - imag[b] * sin;
+                let t_im = real[b] * sin + imag[b] * cos;
+                real[b] = real[a] - t_re;
+                imag[b] = imag[a] - t_im;
+                real[a] += t_re;
+                imag[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}